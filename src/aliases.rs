@@ -0,0 +1,214 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+const CONFIG_FILES: [&str; 2] = ["tsconfig.json", "jsconfig.json"];
+const RESOLVE_SUFFIXES: [&str; 9] = [
+    "",
+    ".ts",
+    ".tsx",
+    ".d.ts",
+    ".js",
+    ".jsx",
+    "/index.ts",
+    "/index.tsx",
+    "/index.js",
+];
+
+static IMPORT_SPECIFIER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\bfrom\s+|\bimport\s*\(?\s*|\brequire\s*\(\s*)["']([^"']+)["']"#).unwrap()
+});
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompilerOptions {
+    base_url: Option<String>,
+    #[serde(default)]
+    paths: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectConfig {
+    #[serde(default)]
+    compiler_options: CompilerOptions,
+}
+
+/// Path aliases read from `compilerOptions.paths` of a `tsconfig.json` or `jsconfig.json`.
+#[derive(Debug)]
+pub struct AliasResolver {
+    root: PathBuf,
+    base_dir: PathBuf,
+    paths: BTreeMap<String, Vec<String>>,
+}
+
+impl AliasResolver {
+    /// Loads aliases from the repository root.
+    ///
+    /// Returns `None` when no config exists, it cannot be parsed, or it
+    /// declares no `paths`, in which case imports are left as written.
+    pub fn load(root: &Path) -> Option<Self> {
+        let config_path = CONFIG_FILES
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.is_file())?;
+        let contents = fs::read_to_string(&config_path).ok()?;
+        let config: ProjectConfig = match serde_json::from_str(&strip_jsonc(&contents)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Ignoring path aliases in {}: {}", config_path.display(), e);
+                return None;
+            }
+        };
+        let options = config.compiler_options;
+        if options.paths.is_empty() {
+            return None;
+        }
+        Some(AliasResolver {
+            root: root.to_path_buf(),
+            base_dir: root.join(options.base_url.as_deref().unwrap_or(".")),
+            paths: options.paths,
+        })
+    }
+
+    /// Resolves an import specifier to a repository-relative path, if it matches an alias.
+    fn resolve(&self, specifier: &str) -> Option<String> {
+        for (pattern, targets) in &self.paths {
+            let captured = match pattern.split_once('*') {
+                Some((prefix, suffix)) => specifier
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix)),
+                None if pattern == specifier => Some(""),
+                None => None,
+            };
+            let Some(captured) = captured else {
+                continue;
+            };
+
+            let candidates: Vec<PathBuf> = targets
+                .iter()
+                .map(|target| self.base_dir.join(target.replacen('*', captured, 1)))
+                .collect();
+            // Prefer a candidate that exists on disk, otherwise report the first mapping.
+            let resolved = candidates
+                .iter()
+                .find_map(|candidate| existing_file(candidate))
+                .or_else(|| candidates.first().cloned())?;
+            return Some(self.relative(&resolved));
+        }
+        None
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                other => normalized.push(other),
+            }
+        }
+        normalized
+            .strip_prefix(&self.root)
+            .unwrap_or(&normalized)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Appends a `// -> path` comment to every import line that uses an alias.
+    pub fn annotate(&self, content: &str) -> String {
+        let mut output = String::with_capacity(content.len());
+        for line in content.split_inclusive('\n') {
+            let resolved: Vec<String> = IMPORT_SPECIFIER
+                .captures_iter(line)
+                .filter_map(|caps| self.resolve(&caps[1]))
+                .collect();
+            if resolved.is_empty() {
+                output.push_str(line);
+                continue;
+            }
+            let (body, newline) = match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            };
+            output.push_str(body);
+            output.push_str(" // -> ");
+            output.push_str(&resolved.join(", "));
+            output.push_str(newline);
+        }
+        output
+    }
+}
+
+/// Whether `path` names a TypeScript or JavaScript source file.
+pub fn is_script(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs")
+    )
+}
+
+fn existing_file(candidate: &Path) -> Option<PathBuf> {
+    RESOLVE_SUFFIXES.iter().find_map(|suffix| {
+        let mut name = candidate.as_os_str().to_os_string();
+        name.push(suffix);
+        let path = PathBuf::from(name);
+        path.is_file().then_some(path)
+    })
+}
+
+/// Removes the comments and trailing commas that tsconfig files allow but JSON does not.
+fn strip_jsonc(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            ('}' | ']', _) => {
+                let trimmed = output.trim_end().len();
+                if output[..trimmed].ends_with(',') {
+                    output.remove(trimmed - 1);
+                }
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
@@ -0,0 +1,81 @@
+use crate::GitRootError;
+use crate::open_files;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Content hashes of the files emitted by a previous run, keyed by relative path.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    files: BTreeMap<String, String>,
+}
+
+impl Baseline {
+    /// The baseline is stored next to the output file as `<output>.baseline.json`.
+    pub fn sidecar_path(output: &Path) -> PathBuf {
+        let mut name = output.as_os_str().to_os_string();
+        name.push(".baseline.json");
+        PathBuf::from(name)
+    }
+
+    pub fn capture(files: &[PathBuf], root: &Path) -> Result<Self, GitRootError> {
+        let mut baseline = Baseline::default();
+        for abs_path in files {
+            let Ok(relative_path) = abs_path.strip_prefix(root) else {
+                continue;
+            };
+            let bytes = open_files::read(abs_path)
+                .map_err(|e| GitRootError::FileRead(abs_path.to_path_buf(), e))?;
+            baseline.files.insert(
+                relative_path.to_string_lossy().replace('\\', "/"),
+                content_hash(&bytes),
+            );
+        }
+        Ok(baseline)
+    }
+
+    /// Returns `None` when no baseline has been written yet.
+    pub fn load(path: &Path) -> Result<Option<Self>, GitRootError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents =
+            fs::read_to_string(path).map_err(|e| GitRootError::FileRead(path.to_path_buf(), e))?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GitRootError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).map_err(|e| GitRootError::FileWrite(path.to_path_buf(), e))
+    }
+
+    /// Paths that are new or whose content differs from `previous`.
+    pub fn changed_since(&self, previous: &Baseline) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter(|(path, hash)| previous.files.get(*path) != Some(*hash))
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+
+    /// Paths present in `previous` that are no longer emitted.
+    pub fn removed_since<'a>(&self, previous: &'a Baseline) -> Vec<&'a str> {
+        previous
+            .files
+            .keys()
+            .filter(|path| !self.files.contains_key(*path))
+            .map(|path| path.as_str())
+            .collect()
+    }
+}
+
+/// 64-bit FNV-1a, chosen because it is stable across Rust releases.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
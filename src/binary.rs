@@ -0,0 +1,214 @@
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How aggressively files are classified as binary and left out of the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BinaryDetect {
+    /// A NUL byte or a high share of control bytes in the scan window.
+    Strict,
+    /// A NUL byte in the scan window, unless the file looks like UTF-16 text.
+    #[default]
+    Lenient,
+    /// Never treat a file as binary, whatever `.gitattributes` says.
+    Off,
+}
+
+pub const DEFAULT_SCAN_BYTES: usize = 8192;
+
+/// Share of non-printable bytes above which `strict` calls a file binary.
+const STRICT_CONTROL_RATIO: f64 = 0.3;
+
+/// Extensions of formats that are binary even when their first bytes look like text.
+const BINARY_EXTENSIONS: [&str; 37] = [
+    "7z", "a", "avi", "bmp", "bz2", "class", "db", "dll", "dylib", "eot", "exe", "flac", "gif",
+    "gz", "ico", "jar", "jpeg", "jpg", "lib", "mkv", "mov", "mp3", "mp4", "o", "ogg", "otf", "pdf",
+    "png", "pyc", "rar", "so", "sqlite", "tgz", "ttf", "wasm", "webp", "zip",
+];
+
+/// The repository's `.gitattributes`, consulted before looking at a file's bytes.
+pub struct GitAttributes {
+    /// Locked because content is read from several threads at once.
+    repo: Mutex<Repository>,
+    workdir: PathBuf,
+}
+
+impl fmt::Debug for GitAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitAttributes")
+            .field("workdir", &self.workdir)
+            .finish()
+    }
+}
+
+impl GitAttributes {
+    pub fn open(root: &Path) -> Option<Self> {
+        let repo = Repository::open(root).ok()?;
+        let workdir = repo.workdir()?.to_path_buf();
+        Some(GitAttributes {
+            repo: Mutex::new(repo),
+            workdir,
+        })
+    }
+
+    /// `Some(true)` for `binary`, `-text` or `-diff`, `Some(false)` for `text`,
+    /// and `None` when the attributes say nothing.
+    fn is_binary(&self, abs_path: &Path) -> Option<bool> {
+        let relative_path = abs_path.strip_prefix(&self.workdir).unwrap_or(abs_path);
+        let repo = self.repo.lock().ok()?;
+        let attr = |name| {
+            repo.get_attr(relative_path, name, AttrCheckFlags::FILE_THEN_INDEX)
+                .map(AttrValue::from_string)
+                .unwrap_or(AttrValue::Unspecified)
+        };
+        if attr("binary") == AttrValue::True {
+            return Some(true);
+        }
+        match attr("text") {
+            AttrValue::True => return Some(false),
+            AttrValue::False => return Some(true),
+            _ => {}
+        }
+        (attr("diff") == AttrValue::False).then_some(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryDetector<'a> {
+    pub mode: BinaryDetect,
+    pub scan_bytes: usize,
+    pub attributes: Option<&'a GitAttributes>,
+}
+
+impl Default for BinaryDetector<'_> {
+    fn default() -> Self {
+        BinaryDetector {
+            mode: BinaryDetect::default(),
+            scan_bytes: DEFAULT_SCAN_BYTES,
+            attributes: None,
+        }
+    }
+}
+
+impl BinaryDetector<'_> {
+    /// The single binary classification used by every output format.
+    ///
+    /// `.gitattributes` wins when it marks the file either way; otherwise the
+    /// file is binary if its scan window looks binary or its extension names a
+    /// binary format.
+    pub fn classify_file(&self, abs_path: &Path, bytes: &[u8]) -> bool {
+        if self.mode == BinaryDetect::Off {
+            return false;
+        }
+        if let Some(binary) = self.attributes.and_then(|attrs| attrs.is_binary(abs_path)) {
+            return binary;
+        }
+        self.is_binary(bytes) || has_binary_extension(abs_path)
+    }
+
+    fn is_binary(&self, bytes: &[u8]) -> bool {
+        let window = &bytes[..bytes.len().min(self.scan_bytes)];
+        match self.mode {
+            BinaryDetect::Off => false,
+            BinaryDetect::Lenient => window.contains(&0) && !looks_like_utf16(window),
+            BinaryDetect::Strict => {
+                window.contains(&0) || control_ratio(window) > STRICT_CONTROL_RATIO
+            }
+        }
+    }
+}
+
+fn has_binary_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| BINARY_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// A UTF-16 byte-order mark, or NULs confined to every other byte as in ASCII-range UTF-16.
+fn looks_like_utf16(window: &[u8]) -> bool {
+    if window.starts_with(&[0xFF, 0xFE]) || window.starts_with(&[0xFE, 0xFF]) {
+        return true;
+    }
+    let nul_at = |parity: usize| {
+        window
+            .iter()
+            .enumerate()
+            .filter(|(i, b)| i % 2 == parity && **b == 0)
+            .count()
+    };
+    let (even, odd) = (nul_at(0), nul_at(1));
+    let pairs = window.len() / 2;
+    pairs > 0 && (even == 0 || odd == 0) && even.max(odd) * 2 >= pairs
+}
+
+fn control_ratio(window: &[u8]) -> f64 {
+    if window.is_empty() {
+        return 0.0;
+    }
+    let control = window
+        .iter()
+        .filter(|b| b.is_ascii_control() && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0C))
+        .count();
+    control as f64 / window.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(mode: BinaryDetect, scan_bytes: usize) -> BinaryDetector<'static> {
+        BinaryDetector {
+            mode,
+            scan_bytes,
+            attributes: None,
+        }
+    }
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    /// Plain text followed by a NUL well past the start.
+    fn late_nul() -> Vec<u8> {
+        let mut bytes = b"text\n".repeat(2000);
+        bytes.extend([0, 1, 2, 3]);
+        bytes
+    }
+
+    #[test]
+    fn utf16_text_is_binary_only_under_strict() {
+        let bytes = utf16le("hello, world\n");
+        let path = Path::new("notes.txt");
+        assert!(!detector(BinaryDetect::Lenient, DEFAULT_SCAN_BYTES).classify_file(path, &bytes));
+        assert!(detector(BinaryDetect::Strict, DEFAULT_SCAN_BYTES).classify_file(path, &bytes));
+        assert!(!detector(BinaryDetect::Off, DEFAULT_SCAN_BYTES).classify_file(path, &bytes));
+    }
+
+    #[test]
+    fn a_late_nul_is_found_only_inside_the_scan_window() {
+        let bytes = late_nul();
+        let path = Path::new("data.txt");
+        assert!(!detector(BinaryDetect::Lenient, DEFAULT_SCAN_BYTES).classify_file(path, &bytes));
+        assert!(detector(BinaryDetect::Lenient, bytes.len()).classify_file(path, &bytes));
+        assert!(detector(BinaryDetect::Strict, bytes.len()).classify_file(path, &bytes));
+        assert!(!detector(BinaryDetect::Off, bytes.len()).classify_file(path, &bytes));
+    }
+
+    #[test]
+    fn strict_catches_control_bytes_without_nuls() {
+        let bytes: Vec<u8> = (1..32).cycle().take(512).collect();
+        let path = Path::new("blob.txt");
+        assert!(!detector(BinaryDetect::Lenient, DEFAULT_SCAN_BYTES).classify_file(path, &bytes));
+        assert!(detector(BinaryDetect::Strict, DEFAULT_SCAN_BYTES).classify_file(path, &bytes));
+    }
+
+    #[test]
+    fn binary_extensions_count_unless_detection_is_off() {
+        let path = Path::new("logo.PNG");
+        assert!(detector(BinaryDetect::Lenient, DEFAULT_SCAN_BYTES).classify_file(path, b"text"));
+        assert!(!detector(BinaryDetect::Off, DEFAULT_SCAN_BYTES).classify_file(path, b"text"));
+    }
+}
@@ -0,0 +1,47 @@
+use serde::Deserialize;
+
+/// Parses a byte count with an optional `k`, `M` or `G` suffix (powers of 1024),
+/// e.g. `500k` or `2M`.
+pub fn parse(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (digits, suffix) = text.split_at(split);
+    let multiplier: u64 = match suffix.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return Err(format!("unknown size suffix in {:?}", text)),
+    };
+    digits
+        .parse::<u64>()
+        .map_err(|_| format!("expected a size like 500k or 2M, got {:?}", text))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size {:?} is too large", text))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSize {
+    Bytes(u64),
+    Text(String),
+}
+
+/// A size in the presets file, written either as a number of bytes or as a
+/// string such as `"500k"`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "RawSize")]
+pub struct ByteSize(pub u64);
+
+impl TryFrom<RawSize> for ByteSize {
+    type Error = String;
+
+    fn try_from(raw: RawSize) -> Result<Self, Self::Error> {
+        match raw {
+            RawSize::Bytes(bytes) => Ok(ByteSize(bytes)),
+            RawSize::Text(text) => parse(&text).map(ByteSize),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::GitRootError;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The checksum is stored next to the output file as `<output>.sha256`.
+pub fn sidecar_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_os_string();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Hashes the file as written and records it in `sha256sum` format, so
+/// `sha256sum -c` run next to the output can verify it.
+pub fn write_sidecar(output: &Path) -> Result<PathBuf, GitRootError> {
+    let bytes = fs::read(output).map_err(|e| GitRootError::FileRead(output.to_path_buf(), e))?;
+    let mut line = String::new();
+    for byte in Sha256::digest(&bytes) {
+        let _ = write!(line, "{:02x}", byte);
+    }
+    let file_name = output
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let _ = writeln!(line, "  {}", file_name);
+
+    let path = sidecar_path(output);
+    fs::write(&path, line).map_err(|e| GitRootError::FileWrite(path.clone(), e))?;
+    Ok(path)
+}
@@ -0,0 +1,106 @@
+use crate::GitRootError;
+
+/// Set in the environment of the background process that keeps the copied
+/// text on the Linux clipboard, which it reads from stdin.
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+const HOLDER_ENV_VAR: &str = "CODE_CONTEXT_CLIPBOARD_HOLDER";
+
+/// The line the holder prints once it has opened the clipboard.
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+const HOLDER_READY: &str = "ok";
+
+/// Places `text` on the system clipboard.
+///
+/// On Linux the clipboard is served by whichever process set it, so the text
+/// is handed to a background copy of this program that keeps serving it until
+/// something else is copied. It fails when that copy cannot open the
+/// clipboard, as on a headless machine.
+#[cfg(feature = "clipboard")]
+pub fn copy_to_clipboard(text: &str) -> Result<(), GitRootError> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::io::{BufRead, BufReader, Write};
+        use std::process::{Command, Stdio};
+
+        let clipboard_error = |e: std::io::Error| GitRootError::Clipboard(e.to_string());
+        let mut holder = std::env::current_exe()
+            .and_then(|program| {
+                Command::new(program)
+                    .env(HOLDER_ENV_VAR, "1")
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::null())
+                    .current_dir("/")
+                    .spawn()
+            })
+            .map_err(clipboard_error)?;
+        // Dropping the pipe closes it, so the holder sees the end of the text.
+        holder
+            .stdin
+            .take()
+            .map_or(Ok(()), |mut stdin| stdin.write_all(text.as_bytes()))
+            .map_err(clipboard_error)?;
+        // The holder answers with one line: `ok` once it owns the clipboard,
+        // otherwise the reason it could not open it.
+        let mut status = String::new();
+        if let Some(stdout) = holder.stdout.take() {
+            BufReader::new(stdout)
+                .read_line(&mut status)
+                .map_err(clipboard_error)?;
+        }
+        match status.trim_end() {
+            HOLDER_READY => Ok(()),
+            "" => Err(GitRootError::Clipboard(
+                "the clipboard process exited without taking the text".to_string(),
+            )),
+            reason => Err(GitRootError::Clipboard(reason.to_string())),
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|e| GitRootError::Clipboard(e.to_string()))
+    }
+}
+
+/// Always fails: this build has no clipboard support.
+#[cfg(not(feature = "clipboard"))]
+pub fn copy_to_clipboard(_text: &str) -> Result<(), GitRootError> {
+    Err(GitRootError::Clipboard(
+        "built without the \"clipboard\" feature".to_string(),
+    ))
+}
+
+/// When this process is the clipboard holder started by
+/// [`copy_to_clipboard`], serves the text from stdin until the clipboard is
+/// overwritten and exits; otherwise does nothing.
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+pub fn serve_if_holder() {
+    use arboard::SetExtLinux;
+    use std::io::{Read, Write};
+
+    if std::env::var_os(HOLDER_ENV_VAR).is_none() {
+        return;
+    }
+    let mut text = String::new();
+    if std::io::stdin().read_to_string(&mut text).is_err() {
+        std::process::exit(1);
+    }
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("{}", HOLDER_READY);
+    let _ = std::io::stdout().flush();
+    let served = clipboard.set().wait().text(text).is_ok();
+    std::process::exit(if served { 0 } else { 1 });
+}
+
+/// Nothing to serve: only Linux builds with clipboard support hand the text
+/// to a holder process.
+#[cfg(not(all(feature = "clipboard", target_os = "linux")))]
+pub fn serve_if_holder() {}
@@ -0,0 +1,219 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// How a family of languages writes comments and strings.
+#[derive(Debug, Clone, Copy)]
+struct CommentSyntax {
+    name: &'static str,
+    line: &'static str,
+    /// Block comment delimiters, if the languages have them.
+    block: Option<(&'static str, &'static str)>,
+    /// Whether `'` opens a string rather than only a char literal.
+    single_quote_strings: bool,
+    /// Whether `` ` `` opens a (possibly multi-line) string.
+    backtick_strings: bool,
+    /// Whether `"""` and `'''` open multi-line strings.
+    triple_quote_strings: bool,
+}
+
+const C_FAMILY: CommentSyntax = CommentSyntax {
+    name: "C-family (// and /* */)",
+    line: "//",
+    block: Some(("/*", "*/")),
+    single_quote_strings: false,
+    backtick_strings: false,
+    triple_quote_strings: false,
+};
+
+const SCRIPT_C_FAMILY: CommentSyntax = CommentSyntax {
+    name: "JavaScript-family (// and /* */)",
+    single_quote_strings: true,
+    backtick_strings: true,
+    ..C_FAMILY
+};
+
+const HASH: CommentSyntax = CommentSyntax {
+    name: "hash (#)",
+    line: "#",
+    block: None,
+    single_quote_strings: true,
+    backtick_strings: false,
+    triple_quote_strings: true,
+};
+
+/// Every supported syntax with the extensions (or file names) it applies to.
+const LANGUAGES: [(CommentSyntax, &[&str]); 3] = [
+    (
+        C_FAMILY,
+        &[
+            "c", "h", "cc", "cpp", "cxx", "hpp", "hh", "cs", "java", "kt", "kts", "scala", "swift",
+            "go", "rs", "dart", "proto",
+        ],
+    ),
+    (SCRIPT_C_FAMILY, &["js", "jsx", "mjs", "cjs", "ts", "tsx"]),
+    (
+        HASH,
+        &[
+            "py",
+            "sh",
+            "bash",
+            "zsh",
+            "rb",
+            "pl",
+            "r",
+            "toml",
+            "yaml",
+            "yml",
+            "mk",
+            "Makefile",
+            "Dockerfile",
+        ],
+    ),
+];
+
+fn syntax_for(path: &Path) -> Option<CommentSyntax> {
+    let key = match path.extension() {
+        Some(ext) => ext.to_str()?.to_ascii_lowercase(),
+        None => path.file_name()?.to_str()?.to_string(),
+    };
+    LANGUAGES
+        .iter()
+        .find(|(_, keys)| keys.contains(&key.as_str()))
+        .map(|(syntax, _)| *syntax)
+}
+
+/// The `--strip-comments-languages` listing.
+pub fn render_supported() -> String {
+    let mut output = String::new();
+    for (syntax, keys) in LANGUAGES {
+        let _ = writeln!(output, "{}: {}", syntax.name, keys.join(" "));
+    }
+    output
+}
+
+/// Removes comments from `content` if `path` is a supported file type.
+///
+/// Best effort: strings are skipped so comment markers inside them survive,
+/// lines left blank by a removed comment are dropped, and a leading `#!`
+/// line is kept. Returns `None` for unsupported file types.
+pub fn strip(path: &Path, content: &str) -> Option<String> {
+    let syntax = syntax_for(path)?;
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+    if let Some(shebang) = content.strip_prefix("#!") {
+        let end = shebang.find('\n').map_or(content.len(), |i| i + 3);
+        output.push_str(&content[..end]);
+        rest = &content[end..];
+    }
+
+    let mut line = String::new();
+    let mut stripped_on_line = false;
+    // A line that lost a comment also loses the whitespace before it, and is
+    // dropped entirely when nothing else was on it.
+    let finish_line = |line: &mut String, stripped: &mut bool, output: &mut String| {
+        if *stripped {
+            line.truncate(line.trim_end().len());
+        }
+        if !(*stripped && line.is_empty()) {
+            output.push_str(line);
+            output.push('\n');
+        }
+        line.clear();
+        *stripped = false;
+    };
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            finish_line(&mut line, &mut stripped_on_line, &mut output);
+            rest = &rest[1..];
+            continue;
+        }
+        let at_word_start = line.chars().last().is_none_or(char::is_whitespace);
+        if rest.starts_with(syntax.line) && (syntax.line != "#" || at_word_start) {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            rest = &rest[end..];
+            stripped_on_line = true;
+            continue;
+        }
+        if let Some((open, close)) = syntax.block
+            && rest.starts_with(open)
+        {
+            let body = &rest[open.len()..];
+            let end = body.find(close).map_or(body.len(), |i| i + close.len());
+            stripped_on_line = true;
+            // Keep line structure when the comment ends on a later line.
+            if body[..end].contains('\n') {
+                finish_line(&mut line, &mut stripped_on_line, &mut output);
+                stripped_on_line = true;
+            }
+            rest = &body[end..];
+            continue;
+        }
+        let string_len = string_literal_len(rest, &syntax);
+        if string_len > 0 {
+            let literal = &rest[..string_len];
+            // A multi-line string is copied through line by line.
+            let mut parts = literal.split('\n');
+            if let Some(first) = parts.next() {
+                line.push_str(first);
+            }
+            for part in parts {
+                output.push_str(&line);
+                output.push('\n');
+                line.clear();
+                line.push_str(part);
+            }
+            rest = &rest[string_len..];
+            continue;
+        }
+        line.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if stripped_on_line {
+        line.truncate(line.trim_end().len());
+    }
+    output.push_str(&line);
+    Some(output)
+}
+
+/// Length in bytes of the string or char literal at the start of `text`,
+/// or 0 when `text` does not start one.
+fn string_literal_len(text: &str, syntax: &CommentSyntax) -> usize {
+    if syntax.triple_quote_strings {
+        for quote in ["\"\"\"", "'''"] {
+            if let Some(body) = text.strip_prefix(quote) {
+                return quote.len() + body.find(quote).map_or(body.len(), |i| i + quote.len());
+            }
+        }
+    }
+    let Some(quote) = text.chars().next() else {
+        return 0;
+    };
+    let multiline = match quote {
+        '"' => true,
+        '`' if syntax.backtick_strings => true,
+        '\'' if syntax.single_quote_strings => false,
+        // A char literal such as 'x' or '\n', not a Rust lifetime.
+        '\'' => {
+            let body = &text[1..];
+            let close = if body.starts_with('\\') {
+                body.char_indices().skip(2).find(|(_, c)| *c == '\'')
+            } else {
+                body.char_indices().nth(1).filter(|(_, c)| *c == '\'')
+            };
+            return close.map_or(0, |(i, _)| i + 2);
+        }
+        _ => return 0,
+    };
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if !multiline => return i,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
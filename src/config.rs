@@ -0,0 +1,346 @@
+use crate::GitRootError;
+use crate::byte_size::ByteSize;
+use glob::Pattern;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The presets file layout printed by `--config-schema`.
+pub const SCHEMA: &str = r#"# Presets file: $XDG_CONFIG_HOME/rust-context/presets.toml (or ~/.config/...),
+# or the file named by --config / $CODE_CONTEXT_CONFIG. presets.yaml or presets.yml
+# take the same keys in YAML; presets.toml wins when more than one exists.
+# Unknown keys are rejected.
+
+# Match glob patterns ignoring case unless the CLI says otherwise.
+case_insensitive = false                # optional bool
+
+[presets.<name>]                        # selected with --preset <name> or auto-detected
+extends = "<other>"                     # optional, inherits <other>'s settings; lists are appended
+include = ["src/**"]                    # patterns added to --include
+exclude = ["target/**"]                 # patterns added to --exclude
+include_in_tree = ["Cargo.lock"]        # patterns added to --include-in-tree
+tree_only = ["src/generated/**"]        # patterns added to --tree-only
+max_file_size = "500k"                  # optional, used when --max-file-size is not given
+template = "File {path}:\n{content}"    # optional, used when --template is not given
+document_template = "{tree}\n{files}"   # optional, used when --document-template is not given
+case_insensitive = true                 # optional, overrides the top-level setting
+
+[detect.markers]                        # file at the repository root -> preset name
+"Cargo.toml" = "<name>"
+
+[detect.dirs]                           # glob on the repository folder name -> preset name
+"*-web" = "<name>"
+"#;
+
+/// Include/exclude patterns contributed by a named preset.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preset {
+    /// Preset whose patterns come first, with this preset's appended.
+    pub extends: Option<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_in_tree: Vec<String>,
+    pub tree_only: Vec<String>,
+    /// Contents of larger files are left out unless `--max-file-size` is given.
+    pub max_file_size: Option<ByteSize>,
+    /// Per-file output template, unless `--template` is given.
+    pub template: Option<String>,
+    /// Whole-document output template, unless `--document-template` is given.
+    pub document_template: Option<String>,
+    /// Overrides the file-wide `case_insensitive` while this preset is active.
+    pub case_insensitive: Option<bool>,
+}
+
+/// Rules that pick a preset for a repository whose folder name is not a preset key.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectRules {
+    /// File name at the repository root -> preset name (e.g. `"Cargo.toml" = "rust"`).
+    pub markers: BTreeMap<String, String>,
+    /// Glob matched against the repository folder name -> preset name.
+    pub dirs: BTreeMap<String, String>,
+}
+
+/// The presets file, `~/.config/rust-context/presets.toml` by default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub presets: BTreeMap<String, Preset>,
+    pub detect: DetectRules,
+    /// Match glob patterns ignoring case unless the CLI says otherwise.
+    pub case_insensitive: Option<bool>,
+}
+
+/// The parent's patterns followed by the child's, skipping repeats.
+pub fn merge_patterns(parent: &[String], child: &[String]) -> Vec<String> {
+    let mut merged = parent.to_vec();
+    for pattern in child {
+        if !merged.contains(pattern) {
+            merged.push(pattern.clone());
+        }
+    }
+    merged
+}
+
+/// Presets file names looked for in the config directory, in order of
+/// precedence.
+const FILE_NAMES: [&str; 3] = ["presets.toml", "presets.yaml", "presets.yml"];
+
+impl Config {
+    /// The presets file in the config directory: the first of [`FILE_NAMES`]
+    /// that exists, or `presets.toml` when none does.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        let dir = config_home.join("rust-context");
+        let existing: Vec<PathBuf> = FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+        if let [used, ignored @ ..] = existing.as_slice()
+            && !ignored.is_empty()
+        {
+            let ignored: Vec<String> = ignored.iter().map(|p| p.display().to_string()).collect();
+            eprintln!(
+                "Using presets from {}, ignoring {}",
+                used.display(),
+                ignored.join(", ")
+            );
+        }
+        Some(
+            existing
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| dir.join(FILE_NAMES[0])),
+        )
+    }
+
+    /// Loads the presets file, treating a missing file as an empty config.
+    /// Files ending in `.yaml` or `.yml` are read as YAML, anything else as TOML.
+    ///
+    /// Unknown keys are errors. Presets are parsed one at a time so an error
+    /// can name the preset it came from.
+    pub fn load(path: &Path) -> Result<Self, GitRootError> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let contents =
+            fs::read_to_string(path).map_err(|e| GitRootError::FileRead(path.to_path_buf(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Self::parse_yaml(path, &contents),
+            _ => Self::parse_toml(path, &contents),
+        }
+    }
+
+    fn parse_toml(path: &Path, contents: &str) -> Result<Self, GitRootError> {
+        let invalid = |e| GitRootError::InvalidConfig(path.to_path_buf(), e);
+        let mut table: toml::Table = toml::from_str(contents).map_err(invalid)?;
+        let presets: BTreeMap<String, toml::Value> = match table.remove("presets") {
+            Some(presets) => presets.try_into().map_err(invalid)?,
+            None => BTreeMap::new(),
+        };
+        let mut config: Config = toml::Value::Table(table).try_into().map_err(invalid)?;
+        for (name, value) in presets {
+            let preset = value.try_into().map_err(|e| {
+                GitRootError::InvalidPreset(path.to_path_buf(), name.clone(), Box::new(e))
+            })?;
+            config.presets.insert(name, preset);
+        }
+        Ok(config)
+    }
+
+    fn parse_yaml(path: &Path, contents: &str) -> Result<Self, GitRootError> {
+        let invalid = |e| GitRootError::InvalidYamlConfig(path.to_path_buf(), e);
+        // An empty document is null rather than an empty mapping.
+        let mut mapping: serde_yaml::Mapping = match serde_yaml::from_str(contents) {
+            Ok(serde_yaml::Value::Null) => serde_yaml::Mapping::new(),
+            Ok(value) => serde_yaml::from_value(value).map_err(invalid)?,
+            Err(e) => return Err(invalid(e)),
+        };
+        let presets: BTreeMap<String, serde_yaml::Value> = match mapping.remove("presets") {
+            Some(presets) => serde_yaml::from_value(presets).map_err(invalid)?,
+            None => BTreeMap::new(),
+        };
+        let mut config: Config =
+            serde_yaml::from_value(serde_yaml::Value::Mapping(mapping)).map_err(invalid)?;
+        for (name, value) in presets {
+            let preset = serde_yaml::from_value(value).map_err(|e| {
+                GitRootError::InvalidYamlPreset(path.to_path_buf(), name.clone(), e)
+            })?;
+            config.presets.insert(name, preset);
+        }
+        Ok(config)
+    }
+
+    /// Flattens the `extends` chain of preset `name`. `chain` holds the
+    /// presets already being resolved, so a repeat is a cycle.
+    fn resolve_preset(&self, name: &str, chain: &mut Vec<String>) -> Result<Preset, GitRootError> {
+        if chain.iter().any(|seen| seen == name) {
+            chain.push(name.to_string());
+            return Err(GitRootError::PresetCycle(chain.join(" -> ")));
+        }
+        let preset = self
+            .presets
+            .get(name)
+            .ok_or_else(|| GitRootError::UnknownPreset(name.to_string()))?;
+        let Some(parent) = &preset.extends else {
+            return Ok(preset.clone());
+        };
+        chain.push(name.to_string());
+        let base = self.resolve_preset(parent, chain)?;
+        chain.pop();
+        Ok(Preset {
+            extends: None,
+            include: merge_patterns(&base.include, &preset.include),
+            exclude: merge_patterns(&base.exclude, &preset.exclude),
+            include_in_tree: merge_patterns(&base.include_in_tree, &preset.include_in_tree),
+            tree_only: merge_patterns(&base.tree_only, &preset.tree_only),
+            max_file_size: preset.max_file_size.or(base.max_file_size),
+            template: preset.template.clone().or(base.template),
+            document_template: preset.document_template.clone().or(base.document_template),
+            case_insensitive: preset.case_insensitive.or(base.case_insensitive),
+        })
+    }
+
+    /// Lists each preset with its non-empty pattern lists, inherited ones
+    /// included, for `--list-presets`.
+    pub fn render_presets(&self, path: &Path) -> Result<String, GitRootError> {
+        if self.presets.is_empty() {
+            return Ok(format!("No presets defined in {}\n", path.display()));
+        }
+        let mut output = format!("Presets in {}:\n", path.display());
+        for name in self.presets.keys() {
+            let preset = self.resolve_preset(name, &mut Vec::new())?;
+            output.push_str(&format!("{}\n", name));
+            for (key, patterns) in [
+                ("include", &preset.include),
+                ("exclude", &preset.exclude),
+                ("include_in_tree", &preset.include_in_tree),
+                ("tree_only", &preset.tree_only),
+            ] {
+                if !patterns.is_empty() {
+                    output.push_str(&format!("  {}: {}\n", key, patterns.join(" ")));
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Picks the preset for `root` and flattens its `extends` chain; other
+    /// presets are left unresolved.
+    ///
+    /// An explicit name always wins. Otherwise `[detect.markers]` are tried,
+    /// then `[detect.dirs]`, and finally a preset named after the folder.
+    pub fn select_preset(
+        &self,
+        explicit: Option<&str>,
+        root: &Path,
+    ) -> Result<Option<(&str, Preset)>, GitRootError> {
+        let lookup = |name: &str| {
+            self.presets
+                .get_key_value(name)
+                .map(|(name, _)| Ok((name.as_str(), self.resolve_preset(name, &mut Vec::new())?)))
+        };
+
+        if let Some(name) = explicit {
+            return lookup(name)
+                .ok_or_else(|| GitRootError::UnknownPreset(name.to_string()))?
+                .map(Some);
+        }
+
+        let folder_name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let detected = self
+            .detect
+            .markers
+            .iter()
+            .find(|(marker, _)| root.join(marker).exists())
+            .map(|(_, preset)| preset)
+            .or_else(|| {
+                self.detect
+                    .dirs
+                    .iter()
+                    .find(|(pattern, _)| {
+                        Pattern::new(pattern).is_ok_and(|p| p.matches(&folder_name))
+                    })
+                    .map(|(_, preset)| preset)
+            });
+
+        if let Some(name) = detected {
+            return lookup(name)
+                .ok_or_else(|| GitRootError::UnknownPreset(name.clone()))?
+                .map(Some);
+        }
+
+        lookup(&folder_name).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Config {
+        Config::parse_toml(Path::new("presets.toml"), contents).expect("valid presets")
+    }
+
+    #[test]
+    fn select_preset_flattens_the_extends_chain() {
+        let config = parse(
+            r#"
+            [presets.base]
+            include = ["src/**", "Cargo.toml"]
+            max_file_size = "1k"
+
+            [presets.app]
+            extends = "base"
+            include = ["Cargo.toml", "app/**"]
+            "#,
+        );
+        let (name, preset) = config
+            .select_preset(Some("app"), Path::new("/repo"))
+            .expect("resolves")
+            .expect("selected");
+        assert_eq!(name, "app");
+        assert_eq!(preset.include, ["src/**", "Cargo.toml", "app/**"]);
+        assert_eq!(preset.max_file_size.map(|size| size.0), Some(1024));
+        assert_eq!(preset.extends, None);
+    }
+
+    #[test]
+    fn only_the_selected_preset_is_resolved() {
+        let config = parse(
+            r#"
+            [presets.loop-a]
+            extends = "loop-b"
+
+            [presets.loop-b]
+            extends = "loop-a"
+
+            [presets.orphan]
+            extends = "missing"
+
+            [presets.fine]
+            include = ["*.rs"]
+            "#,
+        );
+        let root = Path::new("/repo");
+        assert!(config.select_preset(Some("fine"), root).is_ok());
+        assert!(matches!(
+            config.select_preset(Some("loop-a"), root),
+            Err(GitRootError::PresetCycle(_))
+        ));
+        assert!(matches!(
+            config.select_preset(Some("orphan"), root),
+            Err(GitRootError::UnknownPreset(name)) if name == "missing"
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::baseline::content_hash;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A file's transformed contents as stored between runs; `content` is `None` for binary files.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedText {
+    pub content: Option<String>,
+    pub lossy: bool,
+}
+
+/// Transformed file contents keyed by path, modification time, size and the
+/// transform settings, so unchanged files are neither re-read nor re-transformed.
+#[derive(Debug)]
+pub struct ContentCache {
+    dir: PathBuf,
+    fingerprint: String,
+}
+
+impl ContentCache {
+    /// `$XDG_CACHE_HOME/rust-context/content`, falling back to `~/.cache`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let cache_home = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(cache_home.join("rust-context").join("content"))
+    }
+
+    /// `fingerprint` must change whenever a setting that affects the transformed output does.
+    pub fn new(dir: PathBuf, fingerprint: &str) -> Self {
+        ContentCache {
+            dir,
+            fingerprint: format!("{}\0{}", env!("CARGO_PKG_VERSION"), fingerprint),
+        }
+    }
+
+    /// The entry name for `abs_path` as it is on disk now, or `None` if it cannot be stat'ed.
+    pub fn key(&self, abs_path: &Path) -> Option<String> {
+        let metadata = fs::metadata(abs_path).ok()?;
+        let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        let identity = format!(
+            "{}\0{}\0{}\0{}",
+            self.fingerprint,
+            abs_path.display(),
+            mtime.as_nanos(),
+            metadata.len()
+        );
+        Some(content_hash(identity.as_bytes()))
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedText> {
+        let bytes = fs::read(self.dir.join(key)).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Stores an entry; a cache that cannot be written only costs the next run its speed-up.
+    pub fn put(&self, key: &str, text: &CachedText) {
+        if let Ok(json) = serde_json::to_vec(text) {
+            let _ = fs::create_dir_all(&self.dir).and_then(|_| fs::write(self.dir.join(key), json));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::GitRootError;
+use serde::Serialize;
+
+/// How `--conversation` lays out the role-tagged messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConversationFormat {
+    /// `<|im_start|>role ... <|im_end|>` blocks.
+    Chatml,
+    /// A JSON array of `{"role", "content"}` objects.
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    role: &'static str,
+    content: &'a str,
+}
+
+/// Wraps the generated context as a user turn, preceded by an optional system turn.
+pub fn wrap(
+    format: ConversationFormat,
+    system: Option<&str>,
+    context: &str,
+) -> Result<String, GitRootError> {
+    let mut messages = Vec::new();
+    if let Some(system) = system {
+        messages.push(Message {
+            role: "system",
+            content: system,
+        });
+    }
+    messages.push(Message {
+        role: "user",
+        content: context,
+    });
+
+    match format {
+        ConversationFormat::Chatml => Ok(messages
+            .iter()
+            .map(|m| format!("<|im_start|>{}\n{}<|im_end|>\n", m.role, m.content))
+            .collect()),
+        ConversationFormat::Json => Ok(serde_json::to_string_pretty(&messages)? + "\n"),
+    }
+}
@@ -0,0 +1,49 @@
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::Encoding;
+
+/// How to decode file bytes that are not already valid UTF-8.
+#[derive(Debug, Clone, Copy)]
+pub enum SourceEncoding {
+    /// Honour a byte-order mark, otherwise guess from the content.
+    Auto,
+    Fixed(&'static Encoding),
+}
+
+impl SourceEncoding {
+    /// Parses `auto` or any WHATWG encoding label (e.g. `latin1`, `shift_jis`).
+    pub fn parse(name: &str) -> Result<Self, String> {
+        if name.eq_ignore_ascii_case("auto") {
+            return Ok(SourceEncoding::Auto);
+        }
+        Encoding::for_label(name.as_bytes())
+            .map(SourceEncoding::Fixed)
+            .ok_or_else(|| format!("unknown encoding '{}'", name))
+    }
+
+    /// Decodes `bytes` to UTF-8, returning `None` if they are malformed in the chosen encoding.
+    pub fn decode(self, bytes: &[u8]) -> Option<String> {
+        let encoding = match self {
+            SourceEncoding::Fixed(encoding) => encoding,
+            SourceEncoding::Auto => {
+                if let Ok(text) = std::str::from_utf8(bytes) {
+                    return Some(text.to_string());
+                }
+                match Encoding::for_bom(bytes) {
+                    Some((encoding, bom_len)) => {
+                        return encoding
+                            .decode_without_bom_handling_and_without_replacement(&bytes[bom_len..])
+                            .map(|text| text.into_owned());
+                    }
+                    None => {
+                        let mut detector = EncodingDetector::new(Iso2022JpDetection::Allow);
+                        detector.feed(bytes, true);
+                        detector.guess(None, Utf8Detection::Deny)
+                    }
+                }
+            }
+        };
+        encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.into_owned())
+    }
+}
@@ -0,0 +1,277 @@
+use crate::formatter::FormatOptions;
+use crate::{ContentSelector, DirVerdict, FileVerdict, FileWalk, GitRootError};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// One filtering rule consulted for a path.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub name: &'static str,
+    /// Whether the rule applied to the path, e.g. a pattern matched.
+    pub matched: bool,
+    pub detail: String,
+}
+
+/// The steps the walk and content selection took for a path. The default
+/// trace records nothing, so the regular run pays nothing for it.
+#[derive(Debug, Default)]
+pub struct Trace {
+    steps: Option<Vec<Step>>,
+}
+
+impl Trace {
+    pub fn recording() -> Self {
+        Trace {
+            steps: Some(Vec::new()),
+        }
+    }
+
+    /// Records a step; `detail` is only formatted when recording.
+    pub fn step(&mut self, name: &'static str, matched: bool, detail: impl FnOnce() -> String) {
+        if let Some(steps) = &mut self.steps {
+            steps.push(Step {
+                name,
+                matched,
+                detail: detail(),
+            });
+        }
+    }
+
+    fn into_steps(self) -> Vec<Step> {
+        self.steps.unwrap_or_default()
+    }
+}
+
+/// Every filtering step recorded for a single path.
+#[derive(Debug)]
+pub struct PathDecision {
+    pub path: String,
+    pub steps: Vec<Step>,
+    pub size: Option<u64>,
+    pub decision: &'static str,
+}
+
+impl PathDecision {
+    fn matched(&self, names: &[&str]) -> bool {
+        self.steps
+            .iter()
+            .any(|step| step.matched && names.contains(&step.name))
+    }
+}
+
+/// Replays the walk and content selection of a regular run for single paths,
+/// using the very same rules.
+pub struct Explainer<'a> {
+    root: &'a Path,
+    walk: FileWalk<'a>,
+    selector: ContentSelector<'a>,
+    options: &'a FormatOptions<'a>,
+}
+
+impl<'a> Explainer<'a> {
+    pub fn new(
+        root: &'a Path,
+        walk: FileWalk<'a>,
+        selector: ContentSelector<'a>,
+        options: &'a FormatOptions<'a>,
+    ) -> Self {
+        Explainer {
+            root,
+            walk,
+            selector,
+            options,
+        }
+    }
+
+    /// Runs `relative_path` through every step of the walk, content selection
+    /// and binary classification.
+    pub fn decide(&self, relative_path: &Path) -> Result<PathDecision, GitRootError> {
+        let mut trace = Trace::recording();
+        let metadata = fs::metadata(self.root.join(relative_path)).ok();
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        let Some(metadata) = metadata else {
+            trace.step("exists", true, || "no".to_string());
+            return Ok(decision(path, trace, None, "skipped"));
+        };
+        // The walk only reaches the path if it descends into every parent.
+        let mut parents: Vec<&Path> = relative_path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .collect();
+        if metadata.is_dir() {
+            parents.insert(0, relative_path);
+        }
+        for dir in parents.into_iter().rev() {
+            if self.walk.dir_verdict(&self.root.join(dir), dir, &mut trace) != DirVerdict::Enter {
+                return Ok(decision(path, trace, None, "skipped"));
+            }
+        }
+        if metadata.is_dir() {
+            return Ok(decision(path, trace, None, "walked"));
+        }
+        let size = Some(metadata.len());
+        let verdict = self.decide_file(relative_path, &mut trace)?;
+        Ok(decision(path, trace, size, verdict))
+    }
+
+    /// The steps for a file whose parent directories are all walked.
+    fn decide_file(
+        &self,
+        relative_path: &Path,
+        trace: &mut Trace,
+    ) -> Result<&'static str, GitRootError> {
+        match self.walk.file_verdict(relative_path, trace)? {
+            FileVerdict::Listed => {}
+            FileVerdict::Ignored if self.walk.lists_ignored_file(relative_path) => {
+                return Ok("tree-only");
+            }
+            FileVerdict::Ignored | FileVerdict::Skipped => return Ok("skipped"),
+        }
+        let abs_path = self.root.join(relative_path);
+        if !self.selector.content_verdict(&abs_path, trace) {
+            return Ok("tree-only");
+        }
+        let bytes = fs::read(&abs_path).map_err(|e| GitRootError::FileRead(abs_path.clone(), e))?;
+        let binary = self.options.binary.classify_file(&abs_path, &bytes);
+        trace.step("binary", binary, || {
+            match (
+                binary,
+                self.options.include_binary,
+                self.options.binary_base64,
+            ) {
+                (false, _, _) => "text",
+                (true, true, _) => "binary, emitted as lossy text",
+                (true, _, true) => "binary, emitted base64-encoded",
+                (true, false, false) => "binary, contents omitted",
+            }
+            .to_string()
+        });
+        Ok(
+            if binary && !self.options.include_binary && !self.options.binary_base64 {
+                "binary"
+            } else {
+                "content"
+            },
+        )
+    }
+
+    /// Decides every path under the root, listing a directory the walk does
+    /// not descend into (or that is ignored as a whole) once.
+    pub fn decide_all(&self) -> Result<Vec<PathDecision>, GitRootError> {
+        let mut decisions = Vec::new();
+        let mut walker = WalkDir::new(self.root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.file_name().to_str() != Some(".git"));
+        while let Some(entry) = walker.next() {
+            let entry = entry?;
+            let Ok(relative_path) = entry.path().strip_prefix(self.root) else {
+                continue;
+            };
+            if relative_path.as_os_str().is_empty() {
+                continue;
+            }
+            let path = relative_path.to_string_lossy().replace('\\', "/");
+            let mut trace = Trace::recording();
+            if entry.file_type().is_dir() {
+                let verdict = self
+                    .walk
+                    .dir_verdict(entry.path(), relative_path, &mut trace);
+                if verdict == DirVerdict::Enter && self.walk.ignores_whole_dir(relative_path) {
+                    trace.step("gitignore", true, || "ignored".to_string());
+                } else if verdict == DirVerdict::Enter {
+                    continue;
+                }
+                decisions.push(decision(format!("{}/", path), trace, None, "skipped"));
+                walker.skip_current_dir();
+                continue;
+            }
+            let size = entry.metadata().ok().map(|m| m.len());
+            let verdict = self.decide_file(relative_path, &mut trace)?;
+            decisions.push(decision(path, trace, size, verdict));
+        }
+        Ok(decisions)
+    }
+}
+
+fn decision(path: String, trace: Trace, size: Option<u64>, decision: &'static str) -> PathDecision {
+    PathDecision {
+        path,
+        steps: trace.into_steps(),
+        size,
+        decision,
+    }
+}
+
+/// Formats a single decision as the step-by-step `--explain` report.
+pub fn render_steps(decision: &PathDecision) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "{}:", decision.path);
+    for step in &decision.steps {
+        let _ = writeln!(report, "  {}: {}", step.name, step.detail);
+    }
+    let _ = writeln!(report, "  decision: {}", decision.decision);
+    report
+}
+
+/// Formats all decisions as an aligned table, or as TSV when `tsv` is set.
+pub fn render_table(decisions: &[PathDecision], tsv: bool) -> String {
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let header = [
+        "path",
+        "gitignored",
+        "excluded",
+        "included",
+        "tree-only",
+        "decision",
+        "size",
+    ];
+    let rows: Vec<[String; 7]> = decisions
+        .iter()
+        .map(|d| {
+            [
+                d.path.clone(),
+                yes_no(d.matched(&["gitignore"])).to_string(),
+                yes_no(d.matched(&["exclude", "force-exclude"])).to_string(),
+                yes_no(d.matched(&["include", "force-include"])).to_string(),
+                yes_no(d.matched(&["include-in-tree", "tree-only"])).to_string(),
+                d.decision.to_string(),
+                d.size
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    let mut output = String::new();
+    if tsv {
+        let _ = writeln!(output, "{}", header.join("\t"));
+        for row in &rows {
+            let _ = writeln!(output, "{}", row.join("\t"));
+        }
+        return output;
+    }
+
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut write_row = |cells: &[&str]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        let _ = writeln!(output, "{}", line.join("  ").trim_end());
+    };
+    write_row(&header);
+    for row in &rows {
+        write_row(&row.each_ref().map(String::as_str));
+    }
+    output
+}
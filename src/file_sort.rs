@@ -0,0 +1,57 @@
+use crate::git_ref::FileSource;
+use crate::natural_sort::EntryOrder;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What the file list, and so the order of the contents, is sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Natural path order, following the tree.
+    #[default]
+    Path,
+    /// Largest file first.
+    Size,
+    /// Most recently modified file first.
+    Mtime,
+}
+
+/// Sorts `files` by `key`, breaking ties in path order; `reverse` flips the
+/// result. A file whose size or metadata cannot be read counts as empty and oldest.
+pub fn sort_files(
+    files: &mut Vec<PathBuf>,
+    key: SortKey,
+    order: EntryOrder,
+    reverse: bool,
+    source: FileSource,
+) {
+    match key {
+        SortKey::Path => files.sort_by(|a, b| order.compare_paths(a, false, b, false)),
+        SortKey::Size => sort_by_stat(files, order, |path| size(path, source)),
+        SortKey::Mtime => sort_by_stat(files, order, modified),
+    }
+    if reverse {
+        files.reverse();
+    }
+}
+
+pub fn size(path: &Path, source: FileSource) -> u64 {
+    source.size(path).unwrap_or(0)
+}
+
+pub fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Sorts by `stat`, largest first, reading each file's metadata once.
+fn sort_by_stat<K: Ord>(files: &mut Vec<PathBuf>, order: EntryOrder, stat: impl Fn(&Path) -> K) {
+    let mut keyed: Vec<(K, PathBuf)> = files.drain(..).map(|path| (stat(&path), path)).collect();
+    keyed.sort_by(|(a_key, a), (b_key, b)| {
+        b_key
+            .cmp(a_key)
+            .then_with(|| order.compare_paths(a, false, b, false))
+    });
+    files.extend(keyed.into_iter().map(|(_, path)| path));
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An external predicate run once per content candidate: exit status 0 keeps
+/// the file's contents, anything else demotes it to tree-only.
+#[derive(Debug)]
+pub struct FilterCommand {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl FilterCommand {
+    /// Splits `command` on whitespace into a program and its leading arguments.
+    pub fn parse(command: &str, timeout: Duration) -> Option<Self> {
+        let mut words = command.split_whitespace().map(str::to_string);
+        Some(FilterCommand {
+            program: words.next()?,
+            args: words.collect(),
+            timeout,
+        })
+    }
+
+    /// Runs the command with `relative_path` appended, from `root`.
+    ///
+    /// Failing to start, timing out or being killed by a signal all count as
+    /// a rejection, with the reason reported on stderr.
+    pub fn accepts(&self, root: &Path, relative_path: &str) -> bool {
+        let spawned = Command::new(&self.program)
+            .args(&self.args)
+            .arg(relative_path)
+            .current_dir(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!(
+                    "Filter command failed to start for {}: {}",
+                    relative_path, e
+                );
+                return false;
+            }
+        };
+
+        let started = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => return status.success(),
+                Ok(None) if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    eprintln!(
+                        "Filter command timed out after {}s for {}",
+                        self.timeout.as_secs(),
+                        relative_path
+                    );
+                    return false;
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(e) => {
+                    eprintln!("Filter command failed for {}: {}", relative_path, e);
+                    return false;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::git_info::GitInfo;
+use serde_json::{Value, json};
+
+/// Front-matter syntax understood by static-site generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FrontMatterFormat {
+    /// `---` delimited YAML.
+    Yaml,
+    /// `+++` delimited TOML.
+    Toml,
+}
+
+/// Generation metadata written ahead of the output.
+#[derive(Debug)]
+pub struct FrontMatter<'a> {
+    pub root: String,
+    pub preset: Option<&'a str>,
+    pub files: usize,
+    pub tokens: usize,
+    pub include: &'a [String],
+    pub include_in_tree: &'a [String],
+    pub tree_only: &'a [String],
+    pub exclude: &'a [String],
+    /// Only set with `--git-info` inside a repository.
+    pub git: Option<&'a GitInfo>,
+    /// Only set with `--front-matter-timestamp`, so output stays deterministic by default.
+    pub generated_at: Option<String>,
+}
+
+impl FrontMatter<'_> {
+    fn fields(&self) -> Vec<(&'static str, Value)> {
+        let mut fields = vec![
+            ("tool", json!(env!("CARGO_PKG_NAME"))),
+            ("version", json!(env!("CARGO_PKG_VERSION"))),
+            ("root", json!(self.root)),
+        ];
+        if let Some(preset) = self.preset {
+            fields.push(("preset", json!(preset)));
+        }
+        fields.extend([
+            ("files", json!(self.files)),
+            ("tokens", json!(self.tokens)),
+            ("include", json!(self.include)),
+            ("include_in_tree", json!(self.include_in_tree)),
+            ("tree_only", json!(self.tree_only)),
+            ("exclude", json!(self.exclude)),
+        ]);
+        if let Some(git) = self.git {
+            if let Some(branch) = &git.branch {
+                fields.push(("git_branch", json!(branch)));
+            }
+            if let Some(commit) = &git.commit {
+                fields.push(("git_commit", json!(commit)));
+            }
+            fields.push(("git_status", json!(git.status())));
+        }
+        if let Some(generated_at) = &self.generated_at {
+            fields.push(("generated_at", json!(generated_at)));
+        }
+        fields
+    }
+
+    /// Renders the delimited block, followed by a blank line.
+    ///
+    /// Values are written as JSON scalars and flow arrays, which both YAML and
+    /// TOML (for strings, integers and string arrays) parse as-is.
+    pub fn render(&self, format: FrontMatterFormat) -> String {
+        let (delimiter, separator) = match format {
+            FrontMatterFormat::Yaml => ("---", ": "),
+            FrontMatterFormat::Toml => ("+++", " = "),
+        };
+        let mut output = format!("{}\n", delimiter);
+        for (key, value) in self.fields() {
+            output.push_str(&format!("{}{}{}\n", key, separator, value));
+        }
+        output.push_str(&format!("{}\n\n", delimiter));
+        output
+    }
+}
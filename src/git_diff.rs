@@ -0,0 +1,317 @@
+use crate::GitRootError;
+use crate::formatter::QuoteStyle;
+use crate::glob_match::{self, IncludeSet};
+use git2::{Delta, DiffOptions, Patch, Repository};
+use glob::{MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const HUNK_CONTEXT_LINES: u32 = 3;
+
+/// Changed hunks for a single file, rendered as a unified diff body.
+#[derive(Debug)]
+pub struct FileHunks {
+    pub path: PathBuf,
+    pub status: &'static str,
+    /// Empty for a binary file, which has no line hunks.
+    pub hunks: String,
+    pub binary: bool,
+}
+
+impl FileHunks {
+    /// Single-letter status shown next to the file in a review tree.
+    pub fn marker(&self) -> char {
+        match self.status {
+            "added" => 'A',
+            "deleted" => 'D',
+            "renamed" => 'R',
+            "copied" => 'C',
+            "typechange" => 'T',
+            _ => 'M',
+        }
+    }
+}
+
+fn delta_status(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Typechange => "typechange",
+        _ => "modified",
+    }
+}
+
+/// Visits the patch of every file that differs between `rev` and the working
+/// tree, keeping only paths that pass the include/exclude globs. Binary files
+/// are visited without a patch.
+fn for_each_patch(
+    repo_root: &Path,
+    rev: &str,
+    includes: &[String],
+    excludes: &[String],
+    match_options: MatchOptions,
+    mut visit: impl FnMut(PathBuf, Delta, Option<&Patch>) -> Result<(), GitRootError>,
+) -> Result<(), GitRootError> {
+    let repo = Repository::open(repo_root)?;
+    let include_patterns = IncludeSet::new(includes, match_options)?;
+    let exclude_patterns: Result<Vec<Pattern>, _> =
+        excludes.iter().map(|s| Pattern::new(s)).collect();
+    let exclude_patterns = exclude_patterns.map_err(GitRootError::InvalidGlob)?;
+
+    let tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| GitRootError::UnknownRef(rev.to_string(), e))?;
+    let mut options = DiffOptions::new();
+    options
+        .context_lines(HUNK_CONTEXT_LINES)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+    for idx in 0..diff.deltas().len() {
+        let Some(patch) = Patch::from_diff(&diff, idx)? else {
+            continue;
+        };
+        let delta = patch.delta();
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        let path = path.to_path_buf();
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        if exclude_patterns
+            .iter()
+            .any(|p| glob_match::matches(p, &path_str, match_options))
+        {
+            continue;
+        }
+        if !include_patterns.matches(&path_str) {
+            continue;
+        }
+        let status = delta.status();
+        let binary = delta.new_file().is_binary() || delta.old_file().is_binary();
+        visit(path, status, (!binary).then_some(&patch))?;
+    }
+    Ok(())
+}
+
+/// Collects the hunks that differ between `rev` and the working tree,
+/// keeping only paths that pass the include/exclude globs.
+pub fn changed_hunks(
+    repo_root: &Path,
+    rev: &str,
+    includes: &[String],
+    excludes: &[String],
+    match_options: MatchOptions,
+) -> Result<Vec<FileHunks>, GitRootError> {
+    let mut files = Vec::new();
+    for_each_patch(
+        repo_root,
+        rev,
+        includes,
+        excludes,
+        match_options,
+        |path, status, patch| {
+            let Some(patch) = patch else {
+                files.push(FileHunks {
+                    path,
+                    status: delta_status(status),
+                    hunks: String::new(),
+                    binary: true,
+                });
+                return Ok(());
+            };
+            let mut hunks = String::new();
+            for hunk_idx in 0..patch.num_hunks() {
+                let (hunk, line_count) = patch.hunk(hunk_idx)?;
+                hunks.push_str(&String::from_utf8_lossy(hunk.header()));
+                for line_idx in 0..line_count {
+                    let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                    let origin = match line.origin() {
+                        origin @ ('+' | '-' | ' ') => origin,
+                        _ => continue,
+                    };
+                    hunks.push(origin);
+                    hunks.push_str(&String::from_utf8_lossy(line.content()));
+                    if !hunks.ends_with('\n') {
+                        hunks.push('\n');
+                    }
+                }
+            }
+
+            if !hunks.is_empty() {
+                files.push(FileHunks {
+                    path,
+                    status: delta_status(status),
+                    hunks,
+                    binary: false,
+                });
+            }
+            Ok(())
+        },
+    )?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Every path, text or binary, that differs between `rev` and the working
+/// tree, untracked files included. Deleted files are left out since there is
+/// nothing left to read.
+pub fn changed_paths(repo_root: &Path, rev: &str) -> Result<HashSet<PathBuf>, GitRootError> {
+    let repo = Repository::open(repo_root)?;
+    let tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| GitRootError::UnknownRef(rev.to_string(), e))?;
+    let mut options = DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() != Delta::Deleted)
+        .filter_map(|delta| delta.new_file().path().map(Path::to_path_buf))
+        .collect())
+}
+
+/// The paths staged in the index relative to `HEAD` (every index entry on
+/// an unborn branch), like `git diff --cached --name-only`. Renames count
+/// under their new path; staged deletions are reported and left out.
+pub fn staged_paths(repo_root: &Path) -> Result<HashSet<PathBuf>, GitRootError> {
+    let repo = Repository::open(repo_root)?;
+    let head_tree = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(_) => None,
+    };
+    let mut options = DiffOptions::new();
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))?;
+    let mut staged = HashSet::new();
+    for delta in diff.deltas() {
+        if delta.status() == Delta::Deleted {
+            if let Some(path) = delta.old_file().path() {
+                eprintln!("Skipping staged deletion of {}", path.display());
+            }
+            continue;
+        }
+        if let Some(path) = delta.new_file().path() {
+            staged.insert(path.to_path_buf());
+        }
+    }
+    Ok(staged)
+}
+
+/// Added/removed line counts for one changed file; a binary file has none.
+#[derive(Debug)]
+pub struct FileStat {
+    pub path: PathBuf,
+    pub added: usize,
+    pub removed: usize,
+    pub binary: bool,
+}
+
+/// Per-file line counts of the changes since `rev`, like `git diff --numstat`.
+pub fn diff_stat(
+    repo_root: &Path,
+    rev: &str,
+    includes: &[String],
+    excludes: &[String],
+    match_options: MatchOptions,
+) -> Result<Vec<FileStat>, GitRootError> {
+    let mut stats = Vec::new();
+    for_each_patch(
+        repo_root,
+        rev,
+        includes,
+        excludes,
+        match_options,
+        |path, _, patch| {
+            let Some(patch) = patch else {
+                stats.push(FileStat {
+                    path,
+                    added: 0,
+                    removed: 0,
+                    binary: true,
+                });
+                return Ok(());
+            };
+            let (_, added, removed) = patch.line_stats()?;
+            if added + removed > 0 {
+                stats.push(FileStat {
+                    path,
+                    added,
+                    removed,
+                    binary: false,
+                });
+            }
+            Ok(())
+        },
+    )?;
+    stats.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stats)
+}
+
+/// Renders the stats as a `<diff_stat>` block with a closing total line.
+pub fn render_diff_stat(stats: &[FileStat], quote_style: QuoteStyle) -> String {
+    let mut output = String::from("<diff_stat>\n");
+    for stat in stats {
+        let src = quote_style.attr("src", &stat.path.to_string_lossy().replace('\\', "/"));
+        if stat.binary {
+            let _ = writeln!(
+                output,
+                "<file{}{}/>",
+                src,
+                quote_style.attr("binary", "true")
+            );
+            continue;
+        }
+        let _ = writeln!(
+            output,
+            "<file{}{}{}/>",
+            src,
+            quote_style.attr("added", &stat.added.to_string()),
+            quote_style.attr("removed", &stat.removed.to_string())
+        );
+    }
+    let _ = writeln!(
+        output,
+        "<total{}{}{}/>",
+        quote_style.attr("files", &stats.len().to_string()),
+        quote_style.attr(
+            "added",
+            &stats.iter().map(|s| s.added).sum::<usize>().to_string()
+        ),
+        quote_style.attr(
+            "removed",
+            &stats.iter().map(|s| s.removed).sum::<usize>().to_string()
+        )
+    );
+    output.push_str("</diff_stat>\n");
+    output
+}
+
+/// Renders each file's hunks as a labeled `<diff>` block, and a binary
+/// file as an empty one marked `binary="true"`.
+pub fn render_hunks(files: &[FileHunks], quote_style: QuoteStyle) -> String {
+    let mut output = String::new();
+    for file in files {
+        let attrs = format!(
+            "{}{}",
+            quote_style.attr("src", &file.path.to_string_lossy().replace('\\', "/")),
+            quote_style.attr("status", file.status)
+        );
+        if file.binary {
+            let _ = writeln!(
+                output,
+                "<diff{}{}/>",
+                attrs,
+                quote_style.attr("binary", "true")
+            );
+        } else {
+            let _ = write!(output, "<diff{}>\n{}</diff>\n", attrs, file.hunks);
+        }
+    }
+    output
+}
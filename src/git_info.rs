@@ -0,0 +1,72 @@
+use crate::formatter::QuoteStyle;
+use git2::{ErrorCode, Repository, StatusOptions};
+use std::path::Path;
+
+/// Where the working tree stands: branch, commit and whether it has changes.
+#[derive(Debug)]
+pub struct GitInfo {
+    /// `None` for a detached HEAD.
+    pub branch: Option<String>,
+    /// Abbreviated hash of HEAD, `None` before the first commit.
+    pub commit: Option<String>,
+    /// Whether any tracked file is modified or an untracked file exists.
+    pub dirty: bool,
+}
+
+impl GitInfo {
+    /// Reads the state of the repository at `root`, or `None` when `root`
+    /// is not in a repository.
+    pub fn collect(root: &Path) -> Option<Self> {
+        let repo = Repository::discover(root).ok()?;
+        let (branch, commit) = match repo.head() {
+            Ok(head) => (
+                head.is_branch()
+                    .then(|| head.shorthand().map(str::to_string))
+                    .flatten(),
+                head.peel_to_commit()
+                    .ok()
+                    .and_then(|commit| commit.as_object().short_id().ok())
+                    .and_then(|id| id.as_str().map(str::to_string)),
+            ),
+            // Before the first commit HEAD names a branch that does not exist yet.
+            Err(e) if e.code() == ErrorCode::UnbornBranch => {
+                let target = repo.find_reference("HEAD").ok()?;
+                let branch = target
+                    .symbolic_target()
+                    .map(|name| name.trim_start_matches("refs/heads/").to_string());
+                (branch, None)
+            }
+            Err(_) => (None, None),
+        };
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        let dirty = repo
+            .statuses(Some(&mut options))
+            .is_ok_and(|statuses| !statuses.is_empty());
+        Some(GitInfo {
+            branch,
+            commit,
+            dirty,
+        })
+    }
+
+    pub fn status(&self) -> &'static str {
+        if self.dirty { "dirty" } else { "clean" }
+    }
+
+    /// The `<git-info>` element, on its own line.
+    pub fn render(&self, quote_style: QuoteStyle) -> String {
+        let mut output = String::from("<git-info");
+        if let Some(branch) = &self.branch {
+            output.push_str(&quote_style.attr("branch", branch));
+        } else {
+            output.push_str(&quote_style.attr("detached", "true"));
+        }
+        if let Some(commit) = &self.commit {
+            output.push_str(&quote_style.attr("commit", commit));
+        }
+        output.push_str(&quote_style.attr("status", self.status()));
+        output.push_str("/>\n");
+        output
+    }
+}
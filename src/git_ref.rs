@@ -0,0 +1,148 @@
+use crate::GitRootError;
+use crate::glob_match::{self, IncludeSet};
+use crate::open_files;
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use glob::{MatchOptions, Pattern};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The files of a commit, read from their blobs instead of the working tree.
+pub struct RefSnapshot {
+    /// Locked because content is read from several threads at once.
+    repo: Mutex<Repository>,
+    root: PathBuf,
+    blobs: BTreeMap<PathBuf, Oid>,
+}
+
+impl fmt::Debug for RefSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RefSnapshot")
+            .field("root", &self.root)
+            .field("files", &self.blobs.len())
+            .finish()
+    }
+}
+
+impl RefSnapshot {
+    pub fn open(root: &Path, rev: &str) -> Result<Self, GitRootError> {
+        let repo = Repository::open(root)?;
+        let mut blobs = BTreeMap::new();
+        {
+            let tree = repo
+                .revparse_single(rev)
+                .and_then(|object| object.peel_to_tree())
+                .map_err(|e| GitRootError::UnknownRef(rev.to_string(), e))?;
+            tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+                if entry.kind() == Some(ObjectType::Blob)
+                    && let Some(name) = entry.name()
+                {
+                    blobs.insert(Path::new(dir).join(name), entry.id());
+                }
+                TreeWalkResult::Ok
+            })?;
+        }
+        Ok(RefSnapshot {
+            repo: Mutex::new(repo),
+            root: root.to_path_buf(),
+            blobs,
+        })
+    }
+
+    /// Absolute paths of the committed files that pass the include/exclude
+    /// globs. A `--force-include` match is listed regardless of them, and a
+    /// `--force-exclude` match never is.
+    pub fn list_files(
+        &self,
+        includes: &[String],
+        excludes: &[String],
+        force_includes: &[String],
+        force_excludes: &[String],
+        match_options: MatchOptions,
+    ) -> Result<Vec<PathBuf>, GitRootError> {
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>, GitRootError> {
+            patterns
+                .iter()
+                .map(|s| Pattern::new(s).map_err(GitRootError::InvalidGlob))
+                .collect()
+        };
+        let include_patterns = IncludeSet::new(includes, match_options)?;
+        let exclude_patterns = compile(excludes)?;
+        let force_include_patterns = compile(force_includes)?;
+        let force_exclude_patterns = compile(force_excludes)?;
+        let any_match = |patterns: &[Pattern], path_str: &str| {
+            patterns
+                .iter()
+                .any(|p| glob_match::matches(p, path_str, match_options))
+        };
+        Ok(self
+            .blobs
+            .keys()
+            .filter(|relative_path| {
+                let path_str = relative_path.to_string_lossy().replace('\\', "/");
+                !any_match(&force_exclude_patterns, &path_str)
+                    && (any_match(&force_include_patterns, &path_str)
+                        || (include_patterns.matches(&path_str)
+                            && !any_match(&exclude_patterns, &path_str)))
+            })
+            .map(|relative_path| self.root.join(relative_path))
+            .collect())
+    }
+
+    fn blob_id(&self, abs_path: &Path) -> Option<Oid> {
+        let relative_path = abs_path.strip_prefix(&self.root).ok()?;
+        self.blobs.get(relative_path).copied()
+    }
+
+    /// The committed bytes of `abs_path`, or `None` when the ref has no such file.
+    pub fn read(&self, abs_path: &Path) -> Option<Vec<u8>> {
+        let oid = self.blob_id(abs_path)?;
+        let repo = self.repo.lock().ok()?;
+        Some(repo.find_blob(oid).ok()?.content().to_vec())
+    }
+
+    /// The committed size of `abs_path`, read from the object header only.
+    pub fn size(&self, abs_path: &Path) -> Option<u64> {
+        let oid = self.blob_id(abs_path)?;
+        let repo = self.repo.lock().ok()?;
+        let (size, _) = repo.odb().ok()?.read_header(oid).ok()?;
+        Some(size as u64)
+    }
+}
+
+/// Where file sizes and contents come from: the working tree, or the commit
+/// given to `--at-ref`. A file missing from the commit is absent, never read
+/// from the working tree instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum FileSource<'a> {
+    #[default]
+    WorkingTree,
+    Ref(&'a RefSnapshot),
+}
+
+impl<'a> FileSource<'a> {
+    pub fn new(snapshot: Option<&'a RefSnapshot>) -> Self {
+        snapshot.map_or(FileSource::WorkingTree, FileSource::Ref)
+    }
+
+    /// The size of `abs_path` in bytes, or `None` when it does not exist.
+    pub fn size(self, abs_path: &Path) -> Option<u64> {
+        match self {
+            FileSource::WorkingTree => fs::metadata(abs_path).ok().map(|m| m.len()),
+            FileSource::Ref(snapshot) => snapshot.size(abs_path),
+        }
+    }
+
+    pub fn read(self, abs_path: &Path) -> Result<Vec<u8>, GitRootError> {
+        let bytes = match self {
+            FileSource::WorkingTree => open_files::read(abs_path),
+            FileSource::Ref(snapshot) => snapshot.read(abs_path).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "not in the --at-ref commit")
+            }),
+        };
+        bytes.map_err(|e| GitRootError::FileRead(abs_path.to_path_buf(), e))
+    }
+}
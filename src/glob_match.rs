@@ -0,0 +1,111 @@
+use glob::{MatchOptions, Pattern};
+
+/// How user-supplied globs are matched: case-sensitively unless
+/// `case_insensitive` is set, in which case ASCII case is ignored.
+pub fn match_options(case_insensitive: bool) -> MatchOptions {
+    MatchOptions {
+        case_sensitive: !case_insensitive,
+        ..MatchOptions::new()
+    }
+}
+
+/// Matches a user-supplied glob against a forward-slash relative path.
+pub fn matches(pattern: &Pattern, path: &str, options: MatchOptions) -> bool {
+    pattern.matches_with(path, options)
+}
+
+/// Include patterns in the order given. A leading `!` takes back what
+/// earlier patterns matched, and the last pattern matching a path decides,
+/// as in `.gitignore`. Without any plain pattern everything starts out
+/// included, so `!*.md` alone means "all but Markdown".
+#[derive(Debug, Clone)]
+pub struct IncludeSet {
+    /// Each pattern with whether it was negated.
+    rules: Vec<(Pattern, bool)>,
+    options: MatchOptions,
+}
+
+impl IncludeSet {
+    pub fn new(patterns: &[String], options: MatchOptions) -> Result<Self, glob::PatternError> {
+        let rules = patterns
+            .iter()
+            .map(|pattern| match pattern.strip_prefix('!') {
+                Some(negated) => Ok((Pattern::new(negated)?, true)),
+                None => Ok((Pattern::new(pattern)?, false)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(IncludeSet { rules, options })
+    }
+
+    /// The last pattern matching `path` (without its `!`), and whether it
+    /// was negated.
+    pub fn last_match(&self, path: &str) -> Option<(&str, bool)> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches(pattern, path, self.options))
+            .map(|(pattern, negated)| (pattern.as_str(), *negated))
+    }
+
+    /// How the patterns treat `path`, for `--explain`.
+    pub fn describe(&self, path: &str) -> String {
+        match self.last_match(path) {
+            Some((pattern, true)) => format!("negated by {:?}", format!("!{}", pattern)),
+            Some((pattern, false)) => format!("matched {:?}", pattern),
+            None if self.rules.is_empty() => "no patterns".to_string(),
+            None if self.matches(path) => "no match, only negations given".to_string(),
+            None => "no match".to_string(),
+        }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        match self.last_match(path) {
+            Some((_, negated)) => !negated,
+            None => self.rules.iter().all(|(_, negated)| *negated),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn include_set(patterns: &[&str], case_insensitive: bool) -> IncludeSet {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        IncludeSet::new(&patterns, match_options(case_insensitive)).expect("valid globs")
+    }
+
+    #[test]
+    fn case_sensitivity_comes_from_the_options_given() {
+        let sensitive = include_set(&["*.RS"], false);
+        let insensitive = include_set(&["*.RS"], true);
+        assert!(!sensitive.matches("main.rs"));
+        assert!(insensitive.matches("main.rs"));
+        // Each set keeps its own options, whatever was built after it.
+        assert!(!sensitive.matches("lib.rs"));
+    }
+
+    #[test]
+    fn the_last_matching_pattern_decides() {
+        let includes = include_set(&["src/**", "!src/gen/**", "src/gen/keep.rs"], false);
+        assert!(includes.matches("src/main.rs"));
+        assert!(!includes.matches("src/gen/out.rs"));
+        assert!(includes.matches("src/gen/keep.rs"));
+        assert!(!includes.matches("README.md"));
+        assert_eq!(
+            includes.describe("src/gen/out.rs"),
+            "negated by \"!src/gen/**\""
+        );
+    }
+
+    #[test]
+    fn only_negations_include_everything_else() {
+        let includes = include_set(&["!*.md"], false);
+        assert!(includes.matches("src/main.rs"));
+        assert!(!includes.matches("README.md"));
+        assert_eq!(
+            includes.describe("src/main.rs"),
+            "no match, only negations given"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::git_ref::FileSource;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Maps a file extension to the language it is counted under.
+fn language_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "rs" => "Rust",
+        "py" | "pyi" => "Python",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "swift" => "Swift",
+        "scala" => "Scala",
+        "sh" | "bash" | "zsh" => "Shell",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" | "less" => "CSS",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "lua" => "Lua",
+        "sql" => "SQL",
+        "md" | "markdown" => "Markdown",
+        _ => return None,
+    })
+}
+
+/// The identifier code blocks use to highlight `path` (e.g. `rust`, `cpp`).
+pub fn code_tag(path: &Path) -> Option<&'static str> {
+    Some(match language_for(path)? {
+        "Rust" => "rust",
+        "Python" => "python",
+        "TypeScript" => "typescript",
+        "JavaScript" => "javascript",
+        "Go" => "go",
+        "Java" => "java",
+        "Kotlin" => "kotlin",
+        "C" => "c",
+        "C++" => "cpp",
+        "C#" => "csharp",
+        "Ruby" => "ruby",
+        "PHP" => "php",
+        "Swift" => "swift",
+        "Scala" => "scala",
+        "Shell" => "sh",
+        "HTML" => "html",
+        "CSS" => "css",
+        "Vue" => "vue",
+        "Svelte" => "svelte",
+        "Lua" => "lua",
+        "SQL" => "sql",
+        "Markdown" => "markdown",
+        _ => return None,
+    })
+}
+
+#[derive(Debug)]
+pub struct LanguageStat {
+    pub language: &'static str,
+    pub bytes: u64,
+    pub percent: f64,
+}
+
+/// Totals file sizes per language, largest first; files of unknown languages are left out.
+pub fn language_stats(files: &[PathBuf], source: FileSource) -> Vec<LanguageStat> {
+    let mut totals: HashMap<&'static str, u64> = HashMap::new();
+    for path in files {
+        let Some(language) = language_for(path) else {
+            continue;
+        };
+        let size = source.size(path).unwrap_or(0);
+        *totals.entry(language).or_insert(0) += size;
+    }
+    let grand_total: u64 = totals.values().sum();
+    let mut stats: Vec<LanguageStat> = totals
+        .into_iter()
+        .map(|(language, bytes)| LanguageStat {
+            language,
+            bytes,
+            percent: if grand_total == 0 {
+                0.0
+            } else {
+                bytes as f64 * 100.0 / grand_total as f64
+            },
+        })
+        .collect();
+    stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.language.cmp(b.language)));
+    stats
+}
+
+pub fn render_language_stats(stats: &[LanguageStat]) -> String {
+    let width = stats.iter().map(|s| s.language.len()).max().unwrap_or(0);
+    let mut output = String::new();
+    for stat in stats {
+        let _ = writeln!(
+            output,
+            "{:<width$}  {:>6.2}%  {} bytes",
+            stat.language,
+            stat.percent,
+            stat.bytes,
+            width = width
+        );
+    }
+    output
+}
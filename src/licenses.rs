@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+/// Headers shorter than this are ordinary file comments, not license boilerplate.
+const MIN_HEADER_LINES: usize = 3;
+/// A header must appear in at least this many files before it is worth folding.
+const MIN_SHARED_FILES: usize = 2;
+
+const COMMENT_PREFIXES: [&str; 7] = ["//", "#", "/*", "*", "--", ";", "<!--"];
+
+/// Returns the byte range of the leading comment block, skipping a `#!` shebang line.
+fn leading_comment_span(content: &str) -> (usize, usize) {
+    let mut start = 0;
+    if content.starts_with("#!") {
+        start = content.find('\n').map_or(content.len(), |i| i + 1);
+    }
+
+    let mut end = start;
+    let mut lines = 0;
+    for line in content[start..].split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.trim_end().is_empty() || !COMMENT_PREFIXES.iter().any(|p| trimmed.starts_with(p))
+        {
+            break;
+        }
+        end += line.len();
+        lines += 1;
+    }
+    if lines < MIN_HEADER_LINES {
+        end = start;
+    }
+    (start, end)
+}
+
+fn leading_header(content: &str) -> Option<&str> {
+    let (start, end) = leading_comment_span(content);
+    (end > start).then(|| &content[start..end])
+}
+
+/// License headers shared across files, each emitted once and replaced by a marker.
+#[derive(Debug, Default)]
+pub struct LicenseFolder {
+    headers: Vec<String>,
+}
+
+impl LicenseFolder {
+    /// Collects every leading comment block that is identical in enough files.
+    pub fn detect<'a>(contents: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut order = Vec::new();
+        for content in contents {
+            if let Some(header) = leading_header(content) {
+                let count = counts.entry(header).or_insert(0);
+                if *count == 0 {
+                    order.push(header);
+                }
+                *count += 1;
+            }
+        }
+        LicenseFolder {
+            headers: order
+                .into_iter()
+                .filter(|header| counts[header] >= MIN_SHARED_FILES)
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Replaces a folded header at the top of `content` with `[license header N]`.
+    pub fn fold(&self, content: &str) -> Option<String> {
+        let (start, end) = leading_comment_span(content);
+        let header = &content[start..end];
+        let id = self.headers.iter().position(|h| h == header)? + 1;
+        Some(format!(
+            "{}[license header {}]\n{}",
+            &content[..start],
+            id,
+            &content[end..]
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "// Copyright (c) Example Corp.\n// Licensed under the MIT license.\n// See LICENSE for details.\n";
+
+    #[test]
+    fn folds_a_header_shared_by_several_files() {
+        let a = format!("{}\nfn a() {{}}\n", HEADER);
+        let b = format!("{}\nfn b() {{}}\n", HEADER);
+        let c = "// just a note\nfn c() {}\n".to_string();
+
+        let folder = LicenseFolder::detect([a.as_str(), b.as_str(), c.as_str()]);
+        assert_eq!(folder.headers(), [HEADER]);
+        assert_eq!(
+            folder.fold(&a).as_deref(),
+            Some("[license header 1]\n\nfn a() {}\n")
+        );
+        assert_eq!(folder.fold(&c), None);
+    }
+
+    #[test]
+    fn keeps_the_shebang_above_the_marker() {
+        let script = format!("#!/bin/sh\n{}\necho hi\n", HEADER.replace("//", "#"));
+        let folder = LicenseFolder::detect([script.as_str(), script.as_str()]);
+        assert_eq!(
+            folder.fold(&script).as_deref(),
+            Some("#!/bin/sh\n[license header 1]\n\necho hi\n")
+        );
+    }
+
+    #[test]
+    fn a_header_in_one_file_is_not_folded() {
+        let only = format!("{}fn only() {{}}\n", HEADER);
+        let other = "fn other() {}\n";
+        let folder = LicenseFolder::detect([only.as_str(), other]);
+        assert!(folder.headers().is_empty());
+    }
+}
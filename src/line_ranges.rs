@@ -0,0 +1,117 @@
+use crate::GitRootError;
+use crate::glob_match;
+use glob::{MatchOptions, Pattern};
+use std::fmt::Write as _;
+
+/// Line ranges attached to include patterns, as in `src/big.rs:40-120`.
+#[derive(Debug, Default)]
+pub struct LineRanges {
+    /// Each ranged pattern with its inclusive, 1-based range.
+    rules: Vec<(Pattern, (usize, usize))>,
+    options: MatchOptions,
+}
+
+/// Splits a `:N-M` or `:N` suffix off `pattern`.
+fn split_range(pattern: &str) -> Option<(&str, &str)> {
+    let (glob, range) = pattern.rsplit_once(':')?;
+    let is_range = !range.is_empty()
+        && range.chars().all(|c| c.is_ascii_digit() || c == '-')
+        && range.starts_with(|c: char| c.is_ascii_digit());
+    (is_range && !glob.is_empty()).then_some((glob, range))
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+        None => {
+            let line = range.parse().ok()?;
+            (line, line)
+        }
+    };
+    (start >= 1 && start <= end).then_some((start, end))
+}
+
+impl LineRanges {
+    /// Strips range suffixes from `patterns`, leaving plain globs behind, and
+    /// returns the ranges or `None` when no pattern had one. Negated patterns
+    /// cannot carry a range.
+    pub fn extract(
+        patterns: &mut [String],
+        options: MatchOptions,
+    ) -> Result<Option<Self>, GitRootError> {
+        let mut rules = Vec::new();
+        for pattern in patterns.iter_mut() {
+            if pattern.starts_with('!') {
+                continue;
+            }
+            let Some((glob, range)) = split_range(pattern) else {
+                continue;
+            };
+            let range = parse_range(range)
+                .ok_or_else(|| GitRootError::InvalidLineRange(pattern.clone()))?;
+            let glob = glob.to_string();
+            rules.push((Pattern::new(&glob)?, range));
+            *pattern = glob;
+        }
+        Ok((!rules.is_empty()).then_some(LineRanges { rules, options }))
+    }
+
+    /// The ranges of every ranged pattern matching `path`, sorted with
+    /// overlapping and adjacent ones merged; `None` when no pattern matches.
+    pub fn for_path(&self, path: &str) -> Option<Vec<(usize, usize)>> {
+        let mut ranges: Vec<(usize, usize)> = self
+            .rules
+            .iter()
+            .filter(|(pattern, _)| glob_match::matches(pattern, path, self.options))
+            .map(|(_, range)| *range)
+            .collect();
+        if ranges.is_empty() {
+            return None;
+        }
+        ranges.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Some(merged)
+    }
+}
+
+/// Keeps only the lines in `ranges`, each run preceded by a
+/// `<!-- lines A-B of N -->` marker. Ranges are clamped to the file length;
+/// with `line_numbers` the kept lines carry their numbers in the file.
+pub fn select(content: &str, ranges: &[(usize, usize)], line_numbers: bool) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let total = lines.len();
+    let width = ranges
+        .last()
+        .map_or(0, |(_, end)| (*end).min(total).to_string().len());
+    let mut selected = String::new();
+    for &(start, end) in ranges {
+        let end = end.min(total);
+        if start > end {
+            continue;
+        }
+        let _ = writeln!(selected, "<!-- lines {}-{} of {} -->", start, end, total);
+        for (number, line) in (start..=end).zip(&lines[start - 1..end]) {
+            if line_numbers {
+                let _ = write!(selected, "{:>width$} | ", number, width = width);
+            }
+            selected.push_str(line);
+        }
+        if !selected.ends_with('\n') {
+            selected.push('\n');
+        }
+    }
+    if selected.is_empty() {
+        let _ = writeln!(
+            selected,
+            "<!-- no selected lines, the file has {} lines -->",
+            total
+        );
+    }
+    selected
+}
@@ -1,10 +1,82 @@
+mod aliases;
+mod baseline;
+mod binary;
+mod byte_size;
+mod checksum;
+mod clipboard;
+mod comments;
+mod config;
+mod content_cache;
+mod conversation;
+mod encoding;
+mod explain;
+mod file_sort;
+mod filter_cmd;
+mod formatter;
+mod front_matter;
+mod git_diff;
+mod git_info;
+mod git_ref;
+mod glob_match;
+mod languages;
+mod licenses;
+mod line_ranges;
+mod markup;
+mod natural_sort;
+mod open_files;
+mod overflow;
+mod progress;
+mod secrets;
+mod signatures;
+mod stats;
+mod template;
+mod timestamp;
+mod tokens;
+mod tree_json;
+mod watch;
+
+use aliases::AliasResolver;
+use baseline::Baseline;
+use binary::{BinaryDetect, BinaryDetector, GitAttributes};
 use clap::Parser;
+use config::Config;
+use content_cache::ContentCache;
+use conversation::ConversationFormat;
+use encoding::SourceEncoding;
+use explain::Trace;
+use file_sort::SortKey;
+use filter_cmd::FilterCommand;
+use formatter::{
+    FileNote, FormatOptions, QuoteStyle, ReadFailures, Redactor, RenderedContents,
+    get_file_contents, read_document_files, render_contents, stream_file_records,
+};
+use front_matter::{FrontMatter, FrontMatterFormat};
+use git_info::GitInfo;
+use git_ref::{FileSource, RefSnapshot};
 use git2::Repository;
-use glob::Pattern;
+use glob::{MatchOptions, Pattern};
+use glob_match::IncludeSet;
+use line_ranges::LineRanges;
+use markup::MarkupFormat;
+use natural_sort::EntryOrder;
+use overflow::OverflowGuard;
+use progress::Progress;
 use serde::Serialize;
-use std::collections::{BTreeMap, HashSet};
+use stats::ScanStats;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::fmt::Write as _;
 use std::fs;
+use std::io::Write as _;
+use std::num::NonZeroUsize;
+use std::ops::Range;
 use std::path::{Component, Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use template::OutputTemplate;
 use thiserror::Error;
 use walkdir::{DirEntry, WalkDir};
 
@@ -16,16 +88,56 @@ struct FsNode {
     node_type: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     children: Vec<FsNode>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    collapsed: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    ignored: bool,
+}
+
+/// Why a tree entry is shown without being walked or read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum StubKind {
+    /// A directory skipped by `--skip-dirs-over`.
+    Collapsed,
+    /// A gitignored directory or file listed by `--show-gitignored`.
+    IgnoredDir,
+    IgnoredFile,
+}
+
+/// A tree-only entry that never contributes content.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct TreeStub {
+    path: PathBuf,
+    kind: StubKind,
+}
+
+impl StubKind {
+    fn is_dir(self) -> bool {
+        self != StubKind::IgnoredFile
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StubKind::Collapsed => "collapsed",
+            StubKind::IgnoredDir | StubKind::IgnoredFile => "ignored",
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None, group(
     clap::ArgGroup::new("output_mode")
         .required(false)
-        .args(&["tree", "json"]),
+        .args(&["tree", "json", "tree_json", "language_stats", "stats"]),
 ))]
 struct Cli {
-    /// Glob patterns to include (e.g., "*.rs" "src/**")
+    /// Directory to scan; its repository is used as the root (defaults to the current directory)
+    #[arg(value_name = "PATH")]
+    path: Option<PathBuf>,
+
+    /// Glob patterns to include (e.g., "*.rs" "src/**"); a leading "!" takes back
+    /// what earlier patterns matched (e.g., "src/**" "!src/**/*_test.rs"), and a
+    /// ":START-END" suffix keeps only those lines of the matched files (e.g., "src/big.rs:40-120")
     #[arg(long, short = 'i', num_args(1..))]
     include: Vec<String>,
 
@@ -33,17 +145,431 @@ struct Cli {
     #[arg(long, short = 'e', num_args(1..))]
     exclude: Vec<String>,
 
+    /// Read more exclude globs from PATH, one per line (blank lines and `#` comments skipped)
+    #[arg(long, value_name = "PATH")]
+    exclude_file: Vec<PathBuf>,
+
+    /// Glob patterns always listed with contents, overriding gitignore, --tracked-only and every other filter
+    #[arg(long, value_name = "GLOB", num_args(1..))]
+    force_include: Vec<String>,
+
+    /// Glob patterns never listed, overriding every other filter including --force-include
+    #[arg(long, value_name = "GLOB", num_args(1..))]
+    force_exclude: Vec<String>,
+
     /// Glob patterns to include in tree/json output only
     #[arg(long, num_args(1..))]
     include_in_tree: Vec<String>,
 
+    /// Match glob patterns ignoring case (overrides the presets file)
+    #[arg(long, visible_alias = "ignore-case", conflicts_with = "case_sensitive")]
+    case_insensitive: bool,
+
+    /// Match glob patterns case-sensitively even if the presets file says otherwise
+    #[arg(long)]
+    case_sensitive: bool,
+
+    /// Keep backslashes in glob patterns as-is instead of reading them as path separators
+    #[arg(long)]
+    literal_backslash: bool,
+
+    /// Glob patterns whose matches stay in the tree without contents, even when
+    /// they match --include (e.g. "src/generated/**")
+    #[arg(long, num_args(1..))]
+    tree_only: Vec<String>,
+
+    /// Keep files smaller than this many bytes in the tree without their contents
+    #[arg(long, value_name = "BYTES", default_value_t = 0)]
+    min_file_size: u64,
+
+    /// Keep files larger than this (e.g. 500k, 2M) in the tree without their contents
+    #[arg(long, value_name = "BYTES", value_parser = byte_size::parse)]
+    max_file_size: Option<u64>,
+
+    /// Command run with each content candidate's path appended; a nonzero exit
+    /// keeps the file in the tree but leaves out its contents
+    #[arg(long, value_name = "CMD")]
+    filter_cmd: Option<String>,
+
+    /// Seconds before a --filter-cmd run is killed and its file treated as tree-only
+    #[arg(long, value_name = "SECS", default_value_t = filter_cmd::DEFAULT_TIMEOUT_SECS, requires = "filter_cmd")]
+    filter_cmd_timeout: u64,
+
+    /// Presets file to load instead of the default (also set by CODE_CONTEXT_CONFIG)
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Preset from the presets file whose patterns are added to the CLI ones, see --replace
+    /// (auto-detected from the repository when omitted)
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+
+    /// Let each of --include, --exclude, --include-in-tree and --tree-only, when given,
+    /// replace the preset's list of the same kind instead of adding to it; lists not
+    /// given on the command line still come from the preset
+    #[arg(long)]
+    replace: bool,
+
+    /// Print the file types --strip-comments understands and exit
+    #[arg(long)]
+    strip_comments_languages: bool,
+
+    /// Print the layout of the presets file and exit
+    #[arg(long)]
+    config_schema: bool,
+
+    /// Print each preset in the presets file with its patterns and exit
+    #[arg(long)]
+    list_presets: bool,
+
+    /// Name of the preset applied by `resolve_config`, if any
+    #[arg(skip)]
+    active_preset: Option<String>,
+
     /// Display the file list as a human-readable tree
     #[arg(long)]
     tree: bool,
 
+    /// Collapse chains of single-child directories onto one line in --tree and --json
+    #[arg(long)]
+    compact_tree: bool,
+
+    /// In --tree and --json, list directories first, heaviest included content first
+    #[arg(long)]
+    sort_dirs_by_size: bool,
+
+    /// List each directory's files ahead of its subdirectories (default: subdirectories first)
+    #[arg(long, conflicts_with = "sort_dirs_by_size")]
+    files_first: bool,
+
+    /// Order of the file list and contents; with --tree, size and mtime print a flat list
+    #[arg(long, value_enum, default_value_t = SortKey::Path, conflicts_with = "sort_dirs_by_size")]
+    sort: SortKey,
+
+    /// Reverse the --sort order; with the path order and --tree, reverses each directory's entries
+    #[arg(long)]
+    reverse: bool,
+
+    /// In --tree, show each content file's line count and each directory's total
+    #[arg(long)]
+    tree_lines: bool,
+
+    /// In --tree, show each file's size in an aligned column and each directory's total
+    #[arg(long)]
+    show_sizes: bool,
+
+    /// Render --tree as a nested Markdown checkbox list (`- [ ] path`)
+    #[arg(long, requires = "tree")]
+    tree_checkbox_markdown: bool,
+
     /// Display the file list as a machine-readable JSON tree
     #[arg(long)]
     json: bool,
+
+    /// Display the directory structure as nested JSON nodes with paths and content flags
+    #[arg(long)]
+    tree_json: bool,
+
+    /// Report bytes and percentage per language across the files whose contents would be emitted
+    #[arg(long)]
+    language_stats: bool,
+
+    /// Summarize what would be emitted (file counts, bytes, extensions, largest files) without reading contents
+    #[arg(long)]
+    stats: bool,
+
+    /// End the output with a <summary> element counting files with contents,
+    /// tree-only files and total content bytes
+    #[arg(long, conflicts_with_all = ["tree", "json", "tree_json", "language_stats", "stats", "json_stream", "format", "template", "document_template", "split", "split_by_dir"])]
+    summary: bool,
+
+    /// Render the tree and file contents as an Org-mode, Typst, Markdown or JSON document instead of XML
+    #[arg(long, value_enum, conflicts_with_all = ["tree", "json", "tree_json", "language_stats", "review", "json_stream", "split_by_dir", "append", "meta_header", "diff_stat"])]
+    format: Option<MarkupFormat>,
+
+    /// Start a --format markdown document with a table of contents linking to each included file
+    #[arg(long, requires = "format")]
+    toc: bool,
+
+    /// Per-file output template with {path}, {content}, {lang} and {lines}
+    /// ({{ and }} are literal braces, \n and \t escapes)
+    #[arg(long, value_name = "STRING", conflicts_with_all = ["format", "tree", "json", "tree_json", "language_stats", "review", "json_stream", "split_by_dir", "split", "append"])]
+    template: Option<String>,
+
+    /// Whole-document template with {tree} and {files}, the latter being the rendered --template blocks
+    #[arg(long, value_name = "STRING", conflicts_with_all = ["format", "tree", "json", "tree_json", "language_stats", "review", "json_stream", "split_by_dir", "split", "append"])]
+    document_template: Option<String>,
+
+    /// Redact common secrets (private keys, cloud and API tokens, key-like
+    /// values assigned to secret/token/password names) from file contents
+    #[arg(long)]
+    redact: bool,
+
+    /// File of extra patterns to redact from file contents, one per line
+    /// (prefix a line with "re:" to treat it as a regex)
+    #[arg(long, value_name = "FILE")]
+    redact_from: Option<PathBuf>,
+
+    /// Emit only declarations and signatures for supported languages
+    /// (Rust, Python, TypeScript), eliding bodies
+    #[arg(long)]
+    signatures_only: bool,
+
+    /// Remove comments from C-family and #-comment source files (see --strip-comments-languages)
+    #[arg(long)]
+    strip_comments: bool,
+
+    /// Emit only the changed hunks (with context) of files changed since REF
+    #[arg(long, value_name = "REF", conflicts_with_all = ["tree", "json", "tree_json", "append"])]
+    diff_context_only: Option<String>,
+
+    /// Prepend a <diff_stat> block with per-file added/removed line counts since REF
+    #[arg(long, value_name = "REF", conflicts_with_all = ["tree", "json", "tree_json", "append"])]
+    diff_stat: Option<String>,
+
+    /// Emit contents only for files changed since REF (HEAD when no REF is given), keeping the full tree
+    #[arg(long, value_name = "REF", num_args(0..=1), default_missing_value = "HEAD", conflicts_with_all = ["review", "at_ref"])]
+    changed: Option<String>,
+
+    /// Emit contents only for files staged in the index, keeping the full tree
+    #[arg(long, conflicts_with_all = ["changed", "review", "at_ref"])]
+    staged: bool,
+
+    /// Emit a review prompt for changes since REF: a marked tree, the diffs, and changed files
+    #[arg(long, value_name = "REF", conflicts_with_all = ["tree", "json", "tree_json", "append", "diff_context_only"])]
+    review: Option<String>,
+
+    /// Do not descend into directories with more than N immediate entries; show them collapsed
+    #[arg(long, value_name = "N")]
+    skip_dirs_over: Option<usize>,
+
+    /// Only list files at most N directories below the root (0 = root files only); deeper
+    /// directories are shown collapsed and --include-in-tree does not reach into them
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Descend into symlinked directories, emitting each real file's contents only once;
+    /// links that resolve outside the repository are skipped
+    #[arg(long)]
+    follow_symlinks: bool,
+
+    /// Walk top-level directories in parallel (files are then listed in sorted order)
+    #[arg(long)]
+    parallel_scan: bool,
+
+    /// List gitignored paths in the tree, marked as ignored and never read
+    #[arg(long)]
+    show_gitignored: bool,
+
+    /// Walk files that .gitignore, .git/info/exclude and the global excludes file would hide
+    #[arg(long, conflicts_with = "show_gitignored")]
+    no_gitignore: bool,
+
+    /// Only list files tracked in the git index, skipping untracked files even if not ignored (every --at-ref file is committed, so the two cannot be combined)
+    #[arg(long)]
+    tracked_only: bool,
+
+    /// Also apply the .gitignore rules of another repository (or an ignore file path)
+    #[arg(long, value_name = "REPO")]
+    exclude_by_gitignore_of: Option<PathBuf>,
+
+    /// Annotate TS/JS imports that use tsconfig/jsconfig path aliases with the real file path
+    #[arg(long)]
+    dereference_aliases: bool,
+
+    /// Maximum number of files held open at once (defaults to half the open-file ulimit)
+    #[arg(long, value_name = "N")]
+    max_open_files: Option<usize>,
+
+    /// Emit license headers shared by several files once in a <license> block
+    #[arg(long, conflicts_with_all = ["format", "template", "document_template"])]
+    fold_licenses: bool,
+
+    /// How files are classified as binary (and listed without their contents)
+    #[arg(long, value_enum, default_value_t = BinaryDetect::Lenient)]
+    binary_detect: BinaryDetect,
+
+    /// Number of leading bytes inspected by --binary-detect
+    #[arg(long, value_name = "BYTES", default_value_t = binary::DEFAULT_SCAN_BYTES)]
+    binary_scan_bytes: usize,
+
+    /// Emit the contents of binary files too, replacing invalid UTF-8 and marking them lossy="true"
+    #[arg(long)]
+    include_binary: bool,
+
+    /// Emit the contents of binary files base64-encoded in <file encoding="base64"> blocks
+    /// (--max-file-size still applies)
+    #[arg(long, conflicts_with = "include_binary")]
+    binary_base64: bool,
+
+    /// Replace invalid UTF-8 bytes instead of failing, marking the file lossy="true"
+    #[arg(long)]
+    lossy_utf8: bool,
+
+    /// Leave out file contents once their estimated tokens (after all transforms) exceed N
+    #[arg(long, value_name = "N", conflicts_with_all = ["format", "template", "document_template"])]
+    max_tokens: Option<usize>,
+
+    /// Concatenate files under BYTES into one <small-files> block with `--- path ---` separators
+    #[arg(long, value_name = "BYTES", conflicts_with_all = ["format", "template", "document_template"])]
+    aggregate_small_files: Option<u64>,
+
+    /// Prefix each line of file contents with its right-aligned line number
+    #[arg(long)]
+    line_numbers: bool,
+
+    /// Emit at most N lines of each file, followed by a `... (M more lines truncated)` marker
+    #[arg(long, value_name = "N")]
+    max_lines: Option<usize>,
+
+    /// Survey mode: emit only the first N lines of each file, followed by a
+    /// `<!-- first N of M lines -->` marker (with --max-lines, the smaller limit wins)
+    #[arg(long, value_name = "N")]
+    head: Option<usize>,
+
+    /// Mark every Nth line of each file's contents with a <!-- LN --> anchor comment
+    #[arg(long, value_name = "N", conflicts_with_all = ["format", "template", "document_template"])]
+    anchor_interval: Option<NonZeroUsize>,
+
+    /// Add size (bytes) and lines attributes to each <file> tag, measured on the emitted contents
+    #[arg(long, conflicts_with_all = ["format", "template", "document_template"])]
+    annotate: bool,
+
+    /// Wrap file blocks in a <file-contents> element, emitted even when no file has contents
+    #[arg(long)]
+    always_emit_contents: bool,
+
+    /// Skip files that fail to read, list them in an <errors> block and exit with status 3
+    #[arg(long)]
+    partial_on_error: bool,
+
+    /// Extensions whose file contents are wrapped in CDATA (pass none to disable)
+    #[arg(long, value_name = "EXT", num_args(0..), value_delimiter = ',', default_value = "html,htm,xhtml,xml,xsl,xslt,svg", conflicts_with_all = ["format", "template", "document_template"])]
+    cdata_for: Vec<String>,
+
+    /// Attach TEXT as a note="..." attribute to files matching GLOB (repeatable)
+    #[arg(long, value_name = "GLOB=TEXT", value_parser = FileNote::parse, conflicts_with_all = ["format", "template", "document_template"])]
+    note: Vec<FileNote>,
+
+    /// Re-read and re-transform every file instead of reusing cached results from earlier runs
+    #[arg(long)]
+    no_content_cache: bool,
+
+    /// Warn on stderr when more than N files get their contents emitted
+    #[arg(long, value_name = "N", default_value_t = overflow::DEFAULT_MAX_FILES)]
+    overflow_warn_files: usize,
+
+    /// Warn on stderr when the output is estimated at more than N tokens
+    #[arg(long, value_name = "N", default_value_t = overflow::DEFAULT_MAX_TOKENS)]
+    overflow_warn_tokens: usize,
+
+    /// Never print the large-output warning
+    #[arg(long)]
+    no_overflow_warning: bool,
+
+    /// Decode files from this encoding (e.g. "latin1", "shift_jis", or "auto" to detect)
+    #[arg(long, value_name = "NAME", value_parser = SourceEncoding::parse)]
+    encoding: Option<SourceEncoding>,
+
+    /// Quote character used around XML attribute values
+    #[arg(long, value_enum, default_value_t = QuoteStyle::Double)]
+    quote_style: QuoteStyle,
+
+    /// Wrap the output as role-tagged messages (ChatML tags or a JSON messages array)
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "append")]
+    conversation: Option<ConversationFormat>,
+
+    /// System preamble to place before the context when using --conversation
+    #[arg(long, value_name = "TEXT", requires = "conversation")]
+    system: Option<String>,
+
+    /// Prepend a <meta> block recording the tool version, root and active patterns
+    #[arg(long)]
+    meta_header: bool,
+
+    /// Prepend a <git-info> line with the branch, short commit hash and dirty/clean status
+    /// (added to the front matter instead with --front-matter; omitted outside a repository)
+    #[arg(long, conflicts_with_all = ["format", "template", "document_template", "json_stream"])]
+    git_info: bool,
+
+    /// Include the generation time in the --meta-header block (off for deterministic output)
+    #[arg(long, requires = "meta_header")]
+    meta_timestamp: bool,
+
+    /// Prepend parseable front matter (file count, tokens, root, patterns) for static-site tools
+    #[arg(long, value_enum, conflicts_with_all = ["json", "tree_json", "json_stream", "conversation"])]
+    front_matter: Option<FrontMatterFormat>,
+
+    /// Include the generation time in the --front-matter block (off for deterministic output)
+    #[arg(long, requires = "front_matter")]
+    front_matter_timestamp: bool,
+
+    /// Stream one JSON record per file to stdout (NDJSON), flushing after every line
+    #[arg(long, conflicts_with_all = ["tree", "json", "tree_json", "language_stats", "review", "diff_context_only", "append", "output", "conversation", "fold_licenses", "meta_header"])]
+    json_stream: bool,
+
+    /// Report step by step why PATH (relative to the repo root) is emitted, tree-only or skipped
+    #[arg(long, value_name = "PATH", conflicts_with = "at_ref")]
+    explain: Option<PathBuf>,
+
+    /// Write one bundle per top-level directory (root files go to _root.txt) into --output-dir
+    #[arg(long, requires = "output_dir", conflicts_with_all = ["tree", "json", "tree_json", "review", "diff_context_only", "json_stream", "append", "output", "conversation"])]
+    split_by_dir: bool,
+
+    /// Fail instead of warning when a bundle path would exceed the platform path limit
+    #[arg(long, requires = "split_by_dir")]
+    strict_paths: bool,
+
+    /// Directory that receives the --split-by-dir bundles
+    #[arg(long, value_name = "DIR", requires = "split_by_dir")]
+    output_dir: Option<PathBuf>,
+
+    /// Split the output into chunks of about N tokens, each with the full tree; with
+    /// --output FILE they go to FILE.1, FILE.2, ..., otherwise to stdout as <chunk> sections
+    #[arg(long, value_name = "TOKENS", value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["tree", "json", "tree_json", "language_stats", "review", "json_stream", "split_by_dir", "append", "format", "conversation", "diff_stat", "front_matter"])]
+    split: Option<u64>,
+
+    /// Print the filtering decision for every path as a table instead of generating output
+    #[arg(long, conflicts_with_all = ["explain", "at_ref"])]
+    explain_all: bool,
+
+    /// Print the --explain-all table as tab-separated values
+    #[arg(long, requires = "explain_all")]
+    tsv: bool,
+
+    /// Show a spinner while scanning and a bar while reading contents, on stderr
+    /// (only when stderr is a terminal)
+    #[arg(long)]
+    progress: bool,
+
+    /// Regenerate the output whenever a listed file is added, removed or changed
+    /// (excluded files are ignored); runs until interrupted
+    #[arg(long, conflicts_with_all = ["json_stream", "at_ref", "explain", "explain_all"])]
+    watch: bool,
+
+    /// Read the file list and contents from the commit at REF instead of the working tree
+    #[arg(long, value_name = "REF", conflicts_with_all = ["review", "diff_context_only", "json_stream", "append", "show_gitignored", "skip_dirs_over", "max_depth", "filter_cmd", "tracked_only"])]
+    at_ref: Option<String>,
+
+    /// Copy the output to the system clipboard instead of printing it (falls back to stdout)
+    #[arg(long, conflicts_with = "output")]
+    clipboard: bool,
+
+    /// Write the output to a file instead of stdout ("-" keeps stdout)
+    #[arg(long, short = 'o', value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Skip emitting output identical to the previous --watch run's, or to what --output already holds
+    #[arg(long, conflicts_with = "append")]
+    on_change_only: bool,
+
+    /// Write a sha256sum-compatible <FILE>.sha256 next to --output
+    #[arg(long, requires = "output")]
+    checksum: bool,
+
+    /// Append only changed files to --output as a timestamped <update> block
+    #[arg(long, requires = "output", conflicts_with_all = ["tree", "json", "tree_json"])]
+    append: bool,
 }
 
 #[derive(Debug, Error)]
@@ -63,116 +589,680 @@ enum GitRootError {
     FileRead(PathBuf, #[source] std::io::Error),
     #[error("File content for {0} is not valid UTF-8")]
     InvalidUtf8(PathBuf),
+    #[error("Invalid redaction pattern {0}: {1}")]
+    InvalidRedactionPattern(String, #[source] regex::Error),
+    #[error("Failed to write file {0}: {1}")]
+    FileWrite(PathBuf, #[source] std::io::Error),
+    #[error("Failed to write output: {0}")]
+    OutputWrite(#[source] std::io::Error),
+    #[error("Invalid presets file {0}: {1}")]
+    InvalidConfig(PathBuf, #[source] toml::de::Error),
+    #[error("Invalid preset {1:?} in {0}: {2}")]
+    InvalidPreset(PathBuf, String, #[source] Box<toml::de::Error>),
+    #[error("Invalid presets file {0}: {1}")]
+    InvalidYamlConfig(PathBuf, #[source] serde_yaml::Error),
+    #[error("Invalid preset {1:?} in {0}: {2}")]
+    InvalidYamlPreset(PathBuf, String, #[source] serde_yaml::Error),
+    #[error("Presets file not found: {0}")]
+    ConfigNotFound(PathBuf),
+    #[error("Preset inheritance cycle: {0}")]
+    PresetCycle(String),
+    #[error("Unknown preset: {0}")]
+    UnknownPreset(String),
+    #[error("Ignore file not found: {0}")]
+    IgnoreFileNotFound(PathBuf),
+    #[error("Clipboard unavailable: {0}")]
+    Clipboard(String),
+    #[error("Cannot write output: directory {0} does not exist")]
+    OutputDirMissing(PathBuf),
+    #[error("Output path {0} is {1} characters, over the platform limit")]
+    PathTooLong(PathBuf, usize),
+    #[error("Cannot scan {0}: {1}")]
+    ScanRoot(PathBuf, #[source] std::io::Error),
+    #[error("Cannot scan {0}: not a directory")]
+    ScanRootNotDir(PathBuf),
+    #[error("Reading {0} panicked")]
+    ReadPanicked(PathBuf),
+    #[error("A directory walker thread panicked")]
+    WalkerPanicked,
+    #[error("Cannot resolve git ref {0}: {1}")]
+    UnknownRef(String, #[source] git2::Error),
+    #[error("Invalid {0}: {1}")]
+    InvalidTemplate(&'static str, String),
+    #[error("Invalid line range in include pattern {0} (expected PATTERN:START-END)")]
+    InvalidLineRange(String),
+    #[error("Cannot watch for changes: {0}")]
+    Watch(#[source] notify::Error),
+    #[error("Cannot handle Ctrl-C: {0}")]
+    WatchInterrupt(#[source] ctrlc::Error),
 }
 
-fn find_git_root() -> Result<PathBuf, GitRootError> {
-    let repo = Repository::discover(".")?;
+/// Finds the working tree of the repository containing `start`.
+fn find_git_root(start: &Path) -> Result<PathBuf, GitRootError> {
+    let start =
+        fs::canonicalize(start).map_err(|e| GitRootError::ScanRoot(start.to_path_buf(), e))?;
+    if !start.is_dir() {
+        return Err(GitRootError::ScanRootNotDir(start));
+    }
+    let repo = Repository::discover(&start)?;
     let workdir = repo.workdir().ok_or(GitRootError::BareRepo)?;
     Ok(workdir.to_path_buf())
 }
 
 fn is_git_dir(entry: &DirEntry) -> bool {
-    entry.file_name().to_str().map_or(false, |s| s == ".git")
+    entry.file_name().to_str() == Some(".git")
 }
 
-fn list_non_ignored_files(
-    repo_root: &Path,
-    includes: &[String],
-    excludes: &[String],
-) -> Result<Vec<PathBuf>, GitRootError> {
-    let repo = Repository::open(repo_root)?;
-    let include_patterns: Result<Vec<Pattern>, _> =
-        includes.iter().map(|s| Pattern::new(s)).collect();
-    let include_patterns = include_patterns.map_err(GitRootError::InvalidGlob)?;
-    let exclude_patterns: Result<Vec<Pattern>, _> =
-        excludes.iter().map(|s| Pattern::new(s)).collect();
-    let exclude_patterns = exclude_patterns.map_err(GitRootError::InvalidGlob)?;
-    let mut non_ignored_files = Vec::new();
-    let walker = WalkDir::new(repo_root)
+/// Reads the ignore rules to borrow from another repository.
+///
+/// `source` may be a repository directory, in which case its top-level
+/// `.gitignore` is used, or the path of an ignore file itself.
+fn load_external_ignore_rules(source: &Path) -> Result<String, GitRootError> {
+    let ignore_file = if source.is_dir() {
+        source.join(".gitignore")
+    } else {
+        source.to_path_buf()
+    };
+    if !ignore_file.is_file() {
+        return Err(GitRootError::IgnoreFileNotFound(ignore_file));
+    }
+    fs::read_to_string(&ignore_file).map_err(|e| GitRootError::FileRead(ignore_file, e))
+}
+
+/// Filters applied while walking the repository.
+#[derive(Debug, Default)]
+struct WalkOptions<'a> {
+    includes: &'a [String],
+    excludes: &'a [String],
+    /// How every glob above and below is matched.
+    match_options: MatchOptions,
+    extra_ignore_rules: Option<&'a str>,
+    tracked_only: bool,
+    /// Listed regardless of ignore rules, tracking and globs.
+    force_includes: &'a [String],
+    /// Never listed.
+    force_excludes: &'a [String],
+    /// Directories with more immediate entries than this are not descended into.
+    skip_dirs_over: Option<usize>,
+    /// Files more than this many directories below the root are not walked.
+    max_depth: Option<usize>,
+    /// List gitignored paths as tree stubs instead of dropping them.
+    show_gitignored: bool,
+    /// Consult the repository's ignore rules at all.
+    gitignore: bool,
+    /// Walk top-level directories on worker threads.
+    parallel: bool,
+    /// Descend into symlinked directories.
+    follow_symlinks: bool,
+    /// The order each directory's entries are walked in, so files are
+    /// found in listing order.
+    order: EntryOrder,
+    /// Counts the files as they are found.
+    progress: Progress,
+}
+
+/// The walk options the command line asks for; `includes` holds the
+/// include and include-in-tree patterns together.
+fn walk_options<'a>(
+    cli: &'a Cli,
+    includes: &'a [String],
+    extra_ignore_rules: Option<&'a str>,
+    progress: Progress,
+) -> WalkOptions<'a> {
+    WalkOptions {
+        includes,
+        excludes: &cli.exclude,
+        match_options: glob_match::match_options(cli.case_insensitive),
+        force_includes: &cli.force_include,
+        force_excludes: &cli.force_exclude,
+        extra_ignore_rules,
+        tracked_only: cli.tracked_only,
+        skip_dirs_over: cli.skip_dirs_over,
+        max_depth: cli.max_depth,
+        show_gitignored: cli.show_gitignored,
+        gitignore: !cli.no_gitignore,
+        parallel: cli.parallel_scan,
+        follow_symlinks: cli.follow_symlinks,
+        order: EntryOrder::new(cli.files_first),
+        progress,
+    }
+}
+
+#[derive(Debug, Default)]
+struct FileListing {
+    files: Vec<PathBuf>,
+    /// Collapsed or ignored entries shown in the tree only, by relative path.
+    tree_stubs: Vec<TreeStub>,
+}
+
+/// What the walk does with a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirVerdict {
+    Enter,
+    /// Skipped without a trace in the tree.
+    Prune,
+    /// Skipped, with the directory itself listed as a tree stub.
+    Stub(StubKind),
+}
+
+/// What the walk does with a file in a walked directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileVerdict {
+    Listed,
+    /// Gitignored, which `--show-gitignored` lists as a tree stub.
+    Ignored,
+    Skipped,
+}
+
+/// Lazily walks the repository, yielding the absolute path of every file that
+/// passes the ignore rules and include/exclude globs.
+///
+/// Files come out in directory-walk order, unsorted. Collapsed and ignored
+/// entries are gathered on the side and read with [`FileWalk::into_tree_stubs`]
+/// once the walk is exhausted.
+struct FileWalk<'a> {
+    root: &'a Path,
+    repo: Repository,
+    walker: walkdir::IntoIter,
+    /// Whether directories below the starting one are walked at all.
+    descend: bool,
+    follow_symlinks: bool,
+    order: EntryOrder,
+    /// The root with symlinks resolved, set only when following symlinks.
+    canonical_root: Option<PathBuf>,
+    tracked: Option<HashSet<PathBuf>>,
+    includes: IncludeSet,
+    excludes: Vec<Pattern>,
+    match_options: MatchOptions,
+    force_includes: Vec<Pattern>,
+    force_excludes: Vec<Pattern>,
+    skip_dirs_over: Option<usize>,
+    max_depth: Option<usize>,
+    show_gitignored: bool,
+    gitignore: bool,
+    tree_stubs: Vec<TreeStub>,
+    ignored_files: Vec<PathBuf>,
+}
+
+/// Walks `dir` with each directory's entries in `order`.
+fn sorted_walk(dir: &Path, follow_symlinks: bool, order: EntryOrder) -> walkdir::IntoIter {
+    WalkDir::new(dir)
+        .follow_links(follow_symlinks)
+        .sort_by(move |a, b| {
+            order.compare(
+                &a.file_name().to_string_lossy(),
+                a.file_type().is_dir(),
+                &b.file_name().to_string_lossy(),
+                b.file_type().is_dir(),
+            )
+        })
         .into_iter()
-        .filter_entry(|e| !is_git_dir(e));
-    for entry_result in walker {
-        let entry = entry_result?;
-        if entry.path().is_dir() {
-            continue;
-        }
-        let relative_path = match entry.path().strip_prefix(repo_root) {
-            Ok(p) => p,
-            Err(_) => continue,
+}
+
+impl<'a> FileWalk<'a> {
+    fn new(repo_root: &'a Path, options: &WalkOptions) -> Result<Self, GitRootError> {
+        let repo = Repository::open(repo_root)?;
+        if let Some(rules) = options.extra_ignore_rules {
+            // Rules added here live only in memory and are evaluated relative to repo_root.
+            repo.add_ignore_rule(rules)?;
+        }
+        let tracked: Option<HashSet<PathBuf>> = if options.tracked_only {
+            let index = repo.index()?;
+            Some(
+                index
+                    .iter()
+                    .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).into_owned()))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>, GitRootError> {
+            patterns
+                .iter()
+                .map(|s| Pattern::new(s).map_err(GitRootError::InvalidGlob))
+                .collect()
+        };
+        Ok(FileWalk {
+            root: repo_root,
+            repo,
+            walker: sorted_walk(repo_root, options.follow_symlinks, options.order),
+            descend: true,
+            follow_symlinks: options.follow_symlinks,
+            order: options.order,
+            canonical_root: options
+                .follow_symlinks
+                .then(|| fs::canonicalize(repo_root).ok())
+                .flatten(),
+            tracked,
+            includes: IncludeSet::new(options.includes, options.match_options)?,
+            excludes: compile(options.excludes)?,
+            match_options: options.match_options,
+            force_includes: compile(options.force_includes)?,
+            force_excludes: compile(options.force_excludes)?,
+            skip_dirs_over: options.skip_dirs_over,
+            max_depth: options.max_depth,
+            show_gitignored: options.show_gitignored,
+            gitignore: options.gitignore,
+            tree_stubs: Vec::new(),
+            ignored_files: Vec::new(),
+        })
+    }
+
+    /// Walks only `dir`, a directory below the root, instead of the whole repository.
+    fn starting_at(mut self, dir: &Path) -> Self {
+        self.walker = sorted_walk(dir, self.follow_symlinks, self.order);
+        self
+    }
+
+    /// Yields only the files directly in the root, skipping every directory.
+    fn root_files_only(mut self) -> Self {
+        self.walker = WalkDir::new(self.root).max_depth(1).into_iter();
+        self.descend = false;
+        self
+    }
+
+    /// Whether `entry` is a followed link resolving outside the repository.
+    fn escapes_root(&self, entry: &DirEntry) -> bool {
+        let Some(canonical_root) = &self.canonical_root else {
+            return false;
         };
-        if relative_path.as_os_str().is_empty() {
-            continue;
+        entry.path_is_symlink()
+            && !fs::canonicalize(entry.path()).is_ok_and(|real| real.starts_with(canonical_root))
+    }
+
+    /// Whether the ignore rules hide `relative_path`; always false with `--no-gitignore`.
+    fn is_ignored(&self, relative_path: &Path) -> Result<bool, git2::Error> {
+        if !self.gitignore {
+            return Ok(false);
+        }
+        self.repo.is_path_ignored(relative_path)
+    }
+
+    /// The exclude glob matching the directory itself, as `dir` or `dir/`.
+    fn excluded_dir_by(&self, relative_dir: &Path) -> Option<&str> {
+        let relative_dir_str = relative_dir.to_string_lossy().replace('\\', "/");
+        first_match(&self.excludes, &relative_dir_str, self.match_options).or_else(|| {
+            first_match(
+                &self.excludes,
+                &format!("{}/", relative_dir_str),
+                self.match_options,
+            )
+        })
+    }
+
+    /// The exclude glob matching a parent directory of `relative_path`. Such
+    /// a directory is only walked when a force-include may pick a file out of it.
+    fn excluded_parent_by(&self, relative_path: &Path) -> Option<&str> {
+        if self.force_includes.is_empty() {
+            return None;
+        }
+        relative_path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .find_map(|dir| self.excluded_dir_by(dir))
+    }
+
+    /// Whether every file below `relative_dir` is gitignored and no
+    /// `--force-include` can pick one back out.
+    fn ignores_whole_dir(&self, relative_dir: &Path) -> bool {
+        self.force_includes.is_empty() && self.is_ignored(relative_dir).unwrap_or(false)
+    }
+
+    /// Whether an ignored file is listed as a tree stub by `--show-gitignored`,
+    /// which follows the same include/exclude globs as regular files.
+    fn lists_ignored_file(&self, relative_path: &Path) -> bool {
+        let relative_path_str = relative_path.to_string_lossy().replace('\\', "/");
+        self.show_gitignored
+            && self.includes.matches(&relative_path_str)
+            && first_match(&self.excludes, &relative_path_str, self.match_options).is_none()
+            && first_match(&self.force_excludes, &relative_path_str, self.match_options).is_none()
+    }
+
+    /// Decides whether the walk descends into `relative_dir`, recording the
+    /// rule that stops it in `trace`.
+    fn dir_verdict(&self, abs_dir: &Path, relative_dir: &Path, trace: &mut Trace) -> DirVerdict {
+        // An excluded directory is pruned whole, unless a force-include could
+        // still pick something out from under it.
+        if self.force_includes.is_empty()
+            && let Some(pattern) = self.excluded_dir_by(relative_dir)
+        {
+            trace.step("exclude", true, || {
+                format!("directory {} matched {:?}", relative_dir.display(), pattern)
+            });
+            return DirVerdict::Prune;
+        }
+        if self.is_ignored(relative_dir).unwrap_or(false) {
+            // Everything below an ignored directory is ignored too, so list it once.
+            if self.show_gitignored {
+                trace.step("gitignore", true, || {
+                    format!("directory {} ignored", relative_dir.display())
+                });
+                if self.excluded_dir_by(relative_dir).is_none() {
+                    return DirVerdict::Stub(StubKind::IgnoredDir);
+                }
+                return DirVerdict::Prune;
+            }
+            // Ignored directories are pruned later anyway and should not be reported.
+            return DirVerdict::Enter;
         }
-        if repo.is_path_ignored(relative_path)? {
-            continue;
+        // A directory's files sit as many levels down as it has components.
+        if let Some(max_depth) = self
+            .max_depth
+            .filter(|max_depth| relative_dir.components().count() > *max_depth)
+        {
+            trace.step("max-depth", true, || {
+                format!(
+                    "directory {} is below depth {}, collapsed",
+                    relative_dir.display(),
+                    max_depth
+                )
+            });
+            return DirVerdict::Stub(StubKind::Collapsed);
         }
-        let relative_path_str = match relative_path.to_str() {
-            Some(s) => s.replace('\\', "/"),
-            None => continue,
+        let Some(limit) = self.skip_dirs_over else {
+            return DirVerdict::Enter;
         };
-        let mut is_excluded = false;
-        for pattern in &exclude_patterns {
-            if pattern.matches(&relative_path_str) {
-                is_excluded = true;
-                break;
+        let entry_count = fs::read_dir(abs_dir).map(|d| d.count()).unwrap_or(0);
+        if entry_count > limit {
+            trace.step("skip-dirs-over", true, || {
+                format!(
+                    "directory {} has {} entries (limit {}), collapsed",
+                    relative_dir.display(),
+                    entry_count,
+                    limit
+                )
+            });
+            return DirVerdict::Stub(StubKind::Collapsed);
+        }
+        DirVerdict::Enter
+    }
+
+    /// Returns whether the walk should descend into `relative_dir`, recording a
+    /// tree stub when it is skipped.
+    fn enter_dir(&mut self, abs_dir: &Path, relative_dir: &Path) -> bool {
+        match self.dir_verdict(abs_dir, relative_dir, &mut Trace::default()) {
+            DirVerdict::Enter => true,
+            DirVerdict::Prune => false,
+            DirVerdict::Stub(kind) => {
+                self.tree_stubs.push(TreeStub {
+                    path: relative_dir.to_path_buf(),
+                    kind,
+                });
+                false
+            }
+        }
+    }
+
+    /// Whether the file at `relative_path`, which may no longer exist, is one
+    /// the walk lists, in full or as a `--show-gitignored` stub.
+    fn lists_file(&self, relative_path: &Path) -> bool {
+        let mut trace = Trace::default();
+        let mut parents: Vec<&Path> = relative_path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .collect();
+        parents.reverse();
+        let reached = parents.into_iter().all(|dir| {
+            self.dir_verdict(&self.root.join(dir), dir, &mut trace) == DirVerdict::Enter
+        });
+        reached
+            && match self.file_verdict(relative_path, &mut trace) {
+                Ok(FileVerdict::Listed) => true,
+                Ok(FileVerdict::Ignored) => self.lists_ignored_file(relative_path),
+                Ok(FileVerdict::Skipped) => false,
+                Err(_) => true,
+            }
+    }
+
+    /// Decides whether a file in a walked directory is listed, recording
+    /// every rule consulted in `trace`.
+    fn file_verdict(
+        &self,
+        relative_path: &Path,
+        trace: &mut Trace,
+    ) -> Result<FileVerdict, GitRootError> {
+        let relative_path_str = relative_path.to_string_lossy().replace('\\', "/");
+        // --force-exclude loses to nothing and --force-include beats every
+        // other rule, so both are settled before the regular filters.
+        if !self.force_excludes.is_empty() {
+            let pattern = first_match(&self.force_excludes, &relative_path_str, self.match_options);
+            trace.step("force-exclude", pattern.is_some(), || {
+                describe_match(pattern)
+            });
+            if pattern.is_some() {
+                return Ok(FileVerdict::Skipped);
             }
         }
-        if is_excluded {
-            continue;
+        if !self.force_includes.is_empty() {
+            let pattern = first_match(&self.force_includes, &relative_path_str, self.match_options);
+            trace.step("force-include", pattern.is_some(), || {
+                describe_match(pattern)
+            });
+            if pattern.is_some() {
+                return Ok(FileVerdict::Listed);
+            }
+        }
+        if self.gitignore {
+            let ignored = self.is_ignored(relative_path)?;
+            trace.step("gitignore", ignored, || {
+                if ignored { "ignored" } else { "not ignored" }.to_string()
+            });
+            if ignored {
+                return Ok(FileVerdict::Ignored);
+            }
+        }
+        if let Some(tracked) = &self.tracked {
+            let is_tracked = tracked.contains(relative_path);
+            trace.step("tracked-only", !is_tracked, || {
+                if is_tracked { "tracked" } else { "untracked" }.to_string()
+            });
+            if !is_tracked {
+                return Ok(FileVerdict::Skipped);
+            }
+        }
+        if relative_path.to_str().is_none() {
+            trace.step("utf-8 path", true, || {
+                "not valid UTF-8, skipped".to_string()
+            });
+            return Ok(FileVerdict::Skipped);
+        }
+        let excluded_by = first_match(&self.excludes, &relative_path_str, self.match_options)
+            .or_else(|| self.excluded_parent_by(relative_path));
+        trace.step("exclude", excluded_by.is_some(), || {
+            describe_match(excluded_by)
+        });
+        if excluded_by.is_some() {
+            return Ok(FileVerdict::Skipped);
         }
-        if include_patterns.is_empty() {
-            non_ignored_files.push(entry.path().to_path_buf());
+        let listed = self.includes.matches(&relative_path_str);
+        trace.step("listing", listed, || {
+            format!(
+                "{} (--include and --include-in-tree)",
+                self.includes.describe(&relative_path_str)
+            )
+        });
+        Ok(if listed {
+            FileVerdict::Listed
         } else {
-            let mut is_included = false;
-            for pattern in &include_patterns {
-                if pattern.matches(&relative_path_str) {
-                    is_included = true;
-                    break;
+            FileVerdict::Skipped
+        })
+    }
+
+    /// The collapsed and ignored entries met during the walk, sorted by path.
+    fn into_tree_stubs(mut self) -> Vec<TreeStub> {
+        // Ignored files follow the same include/exclude globs as regular ones.
+        for relative_path in std::mem::take(&mut self.ignored_files) {
+            if self.lists_ignored_file(&relative_path) {
+                self.tree_stubs.push(TreeStub {
+                    path: relative_path,
+                    kind: StubKind::IgnoredFile,
+                });
+            }
+        }
+        self.tree_stubs.sort();
+        self.tree_stubs
+    }
+}
+
+impl Iterator for FileWalk<'_> {
+    type Item = Result<PathBuf, GitRootError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.walker.next()? {
+                Ok(entry) => entry,
+                // A symlink back to an ancestor is walked once, not forever.
+                Err(e) if e.loop_ancestor().is_some() => continue,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if is_git_dir(&entry) {
+                if entry.file_type().is_dir() {
+                    self.walker.skip_current_dir();
+                }
+                continue;
+            }
+            if self.escapes_root(&entry) {
+                if entry.file_type().is_dir() {
+                    self.walker.skip_current_dir();
                 }
+                continue;
+            }
+            let Ok(relative_path) = entry.path().strip_prefix(self.root) else {
+                continue;
+            };
+            let relative_path = relative_path.to_path_buf();
+            if entry.path() != self.root
+                && entry.file_type().is_dir()
+                && (!self.descend || !self.enter_dir(entry.path(), &relative_path))
+            {
+                self.walker.skip_current_dir();
+                continue;
             }
-            if is_included {
-                non_ignored_files.push(entry.path().to_path_buf());
+            if entry.path().is_dir() || relative_path.as_os_str().is_empty() {
+                continue;
+            }
+            match self.file_verdict(&relative_path, &mut Trace::default()) {
+                Ok(FileVerdict::Listed) => return Some(Ok(entry.into_path())),
+                Ok(FileVerdict::Ignored) => {
+                    if self.show_gitignored {
+                        self.ignored_files.push(relative_path);
+                    }
+                }
+                Ok(FileVerdict::Skipped) => {}
+                Err(e) => return Some(Err(e)),
             }
         }
     }
-    Ok(non_ignored_files)
 }
 
-fn get_file_contents(
-    files: &[PathBuf], // Expecting absolute paths from list_non_ignored_files
-    root: &Path,
-) -> Result<String, GitRootError> {
-    let mut final_output = String::new();
-
-    for abs_path in files {
-        let relative_path = match abs_path.strip_prefix(root) {
-            Ok(p) => p,
-            Err(_) => continue, // Should be unreachable
-        };
+/// The first of `patterns` matching `path`, as written.
+fn first_match<'p>(patterns: &'p [Pattern], path: &str, options: MatchOptions) -> Option<&'p str> {
+    patterns
+        .iter()
+        .find(|pattern| glob_match::matches(pattern, path, options))
+        .map(Pattern::as_str)
+}
 
-        // Create a clean, forward-slash path for the tag
-        let relative_path_str = relative_path.to_string_lossy().replace('\\', "/");
+fn describe_match(pattern: Option<&str>) -> String {
+    match pattern {
+        Some(pattern) => format!("matched {:?}", pattern),
+        None => "no match".to_string(),
+    }
+}
 
-        // Read as bytes first to validate UTF-8
-        let content_bytes =
-            fs::read(abs_path).map_err(|e| GitRootError::FileRead(abs_path.to_path_buf(), e))?;
+fn list_non_ignored_files(
+    repo_root: &Path,
+    options: &WalkOptions,
+) -> Result<FileListing, GitRootError> {
+    options.progress.start_scan();
+    let listing = if options.parallel {
+        list_non_ignored_files_parallel(repo_root, options)
+    } else {
+        FileWalk::new(repo_root, options).and_then(|mut walk| {
+            let files = walk
+                .by_ref()
+                .inspect(|_| options.progress.inc())
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(FileListing {
+                files,
+                tree_stubs: walk.into_tree_stubs(),
+            })
+        })
+    };
+    options.progress.finish();
+    listing
+}
 
-        let content_str = String::from_utf8(content_bytes)
-            .map_err(|_| GitRootError::InvalidUtf8(abs_path.to_path_buf()))?;
+/// Same listing as the sequential walk, with each top-level directory walked
+/// on a worker thread. Files are sorted by path since thread order is arbitrary.
+fn list_non_ignored_files_parallel(
+    repo_root: &Path,
+    options: &WalkOptions,
+) -> Result<FileListing, GitRootError> {
+    let mut root_walk = FileWalk::new(repo_root, options)?.root_files_only();
+    let mut files = root_walk.by_ref().collect::<Result<Vec<_>, _>>()?;
+    let mut tree_stubs = root_walk.into_tree_stubs();
 
-        // Append the wrapped content
-        final_output.push_str(&format!(
-            "<file src=\"{}\">\n{}</file>\n",
-            relative_path_str, content_str
-        ));
+    let mut top_dirs = Vec::new();
+    for entry in WalkDir::new(repo_root)
+        .follow_links(options.follow_symlinks)
+        .min_depth(1)
+        .max_depth(1)
+    {
+        let entry = entry?;
+        if entry.file_type().is_dir() && !is_git_dir(&entry) {
+            top_dirs.push(entry.into_path());
+        }
     }
-
-    Ok(final_output)
+    let queue = Mutex::new(top_dirs);
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let results: Vec<Result<FileListing, GitRootError>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut listing = FileListing::default();
+                    while let Some(dir) = queue.lock().ok().and_then(|mut q| q.pop()) {
+                        let mut walk = FileWalk::new(repo_root, options)?.starting_at(&dir);
+                        for file in walk.by_ref() {
+                            listing.files.push(file?);
+                            options.progress.inc();
+                        }
+                        listing.tree_stubs.extend(walk.into_tree_stubs());
+                    }
+                    Ok(listing)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or(Err(GitRootError::WalkerPanicked)))
+            .collect()
+    });
+    for result in results {
+        let listing = result?;
+        files.extend(listing.files);
+        tree_stubs.extend(listing.tree_stubs);
+    }
+    files.sort();
+    tree_stubs.sort();
+    Ok(FileListing { files, tree_stubs })
 }
 
-fn build_fs_tree(relative_files: &[PathBuf]) -> Vec<FsNode> {
-    // Helper function to recursively build the tree
-    fn insert_path(current_level: &mut BTreeMap<String, FsNode>, path_components: &[Component]) {
+fn build_fs_tree(
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    order: EntryOrder,
+) -> Vec<FsNode> {
+    // Helper function to recursively build the tree; `stub` marks the leaf as
+    // an entry that was not walked or read.
+    fn insert_path(
+        current_level: &mut BTreeMap<String, FsNode>,
+        path_components: &[Component],
+        stub: Option<StubKind>,
+    ) {
         if path_components.is_empty() {
             return;
         }
@@ -181,7 +1271,9 @@ fn build_fs_tree(relative_files: &[PathBuf]) -> Vec<FsNode> {
         let name = component.as_os_str().to_string_lossy().to_string();
         let remaining_components = &path_components[1..];
 
-        let is_file = remaining_components.is_empty();
+        let is_leaf = remaining_components.is_empty();
+        let leaf_stub = stub.filter(|_| is_leaf);
+        let is_file = is_leaf && !leaf_stub.is_some_and(StubKind::is_dir);
         let node_type = if is_file { "file" } else { "directory" };
 
         // Find or create the node for the current path component
@@ -189,9 +1281,14 @@ fn build_fs_tree(relative_files: &[PathBuf]) -> Vec<FsNode> {
             name,
             node_type: node_type.to_string(),
             children: Vec::new(),
+            collapsed: leaf_stub == Some(StubKind::Collapsed),
+            ignored: matches!(
+                leaf_stub,
+                Some(StubKind::IgnoredDir | StubKind::IgnoredFile)
+            ),
         });
 
-        if !is_file {
+        if !is_leaf {
             // This is a directory; we need to recurse into its children.
             // We convert the Vec<FsNode> to a BTreeMap to efficiently find/insert
             // the next component.
@@ -202,9 +1299,9 @@ fn build_fs_tree(relative_files: &[PathBuf]) -> Vec<FsNode> {
                 .collect();
 
             // Recurse with the rest of the path
-            insert_path(&mut children_map, remaining_components);
+            insert_path(&mut children_map, remaining_components, stub);
 
-            // Convert the BTreeMap back into a sorted Vec
+            // Convert the BTreeMap back into a Vec
             node.children = children_map.into_values().collect();
         }
     }
@@ -212,37 +1309,1275 @@ fn build_fs_tree(relative_files: &[PathBuf]) -> Vec<FsNode> {
     let mut root: BTreeMap<String, FsNode> = BTreeMap::new();
     for path in relative_files {
         let components: Vec<Component> = path.components().collect();
-        insert_path(&mut root, &components);
+        insert_path(&mut root, &components, None);
+    }
+    for stub in tree_stubs {
+        let components: Vec<Component> = stub.path.components().collect();
+        insert_path(&mut root, &components, Some(stub.kind));
+    }
+
+    // Each level is finally put in natural order, directories and files apart.
+    fn sort_level(nodes: &mut [FsNode], order: EntryOrder) {
+        nodes.sort_by(|a, b| {
+            order.compare(
+                &a.name,
+                a.node_type == "directory",
+                &b.name,
+                b.node_type == "directory",
+            )
+        });
+        for node in nodes {
+            sort_level(&mut node.children, order);
+        }
     }
 
-    // Convert the root map to a sorted Vec for the final JSON array
-    root.into_values().collect()
+    let mut nodes: Vec<FsNode> = root.into_values().collect();
+    sort_level(&mut nodes, order);
+    nodes
 }
 
-fn print_tree_style(relative_files: &[PathBuf]) {
+/// Merges every directory whose only child is another directory into that
+/// child, so `src/com/example/` becomes a single node.
+fn compact_fs_tree(nodes: Vec<FsNode>) -> Vec<FsNode> {
+    nodes
+        .into_iter()
+        .map(|mut node| {
+            node.children = compact_fs_tree(node.children);
+            if node.node_type == "directory"
+                && node.children.len() == 1
+                && node.children[0].node_type == "directory"
+            {
+                let mut child = node.children.remove(0);
+                child.name = format!("{}/{}", node.name, child.name);
+                return child;
+            }
+            node
+        })
+        .collect()
+}
+
+/// Orders each level's directories by the total size of the content files
+/// below them, largest first, ahead of the level's files (which keep their
+/// order). Returns the total for `nodes`.
+fn sort_dirs_by_content_size(
+    nodes: &mut [FsNode],
+    dir: &Path,
+    content_sizes: &HashMap<PathBuf, u64>,
+) -> u64 {
+    let mut totals = HashMap::new();
+    for node in nodes.iter_mut() {
+        let path = dir.join(&node.name);
+        let size = if node.node_type == "directory" {
+            sort_dirs_by_content_size(&mut node.children, &path, content_sizes)
+        } else {
+            content_sizes.get(&path).copied().unwrap_or(0)
+        };
+        totals.insert(node.name.clone(), size);
+    }
+    nodes.sort_by_key(|node| {
+        let is_file = node.node_type != "directory";
+        let size = if is_file { 0 } else { totals[&node.name] };
+        (is_file, Reverse(size))
+    });
+    totals.values().sum()
+}
+
+/// Reverses the name order of each level's directories and of its files,
+/// leaving directories and files in their own groups.
+fn reverse_fs_tree(nodes: &mut [FsNode]) {
+    for node in nodes.iter_mut() {
+        reverse_fs_tree(&mut node.children);
+    }
+    let first_kind = nodes.first().map(|node| node.node_type.clone());
+    let split = nodes
+        .iter()
+        .position(|node| Some(&node.node_type) != first_kind.as_ref())
+        .unwrap_or(nodes.len());
+    let (first_group, second_group) = nodes.split_at_mut(split);
+    first_group.reverse();
+    second_group.reverse();
+}
+
+/// Builds the `--tree`/`--json` node tree with `--reverse`, `--sort-dirs-by-size`
+/// and `--compact-tree` applied. `content_files` is only consulted when sorting.
+fn arranged_fs_tree(
+    cli: &Cli,
+    root: &Path,
+    source: FileSource,
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    content_files: &[PathBuf],
+) -> Vec<FsNode> {
+    let mut tree = build_fs_tree(relative_files, tree_stubs, EntryOrder::new(cli.files_first));
+    if cli.reverse {
+        reverse_fs_tree(&mut tree);
+    }
+    if cli.sort_dirs_by_size {
+        let content_sizes: HashMap<PathBuf, u64> = content_files
+            .iter()
+            .filter_map(|abs_path| {
+                let size = source.size(abs_path)?;
+                Some((abs_path.strip_prefix(root).ok()?.to_path_buf(), size))
+            })
+            .collect();
+        sort_dirs_by_content_size(&mut tree, Path::new(""), &content_sizes);
+    }
+    if cli.compact_tree {
+        tree = compact_fs_tree(tree);
+    }
+    tree
+}
+
+/// Line counts of the text files among `content_files`, by relative path.
+fn count_lines(
+    content_files: &[PathBuf],
+    root: &Path,
+    source: FileSource,
+    binary: &BinaryDetector,
+) -> HashMap<PathBuf, usize> {
+    content_files
+        .iter()
+        .filter_map(|abs_path| {
+            let bytes = source.read(abs_path).ok()?;
+            if binary.classify_file(abs_path, &bytes) {
+                return None;
+            }
+            let newlines = bytes.iter().filter(|b| **b == b'\n').count();
+            let unterminated = usize::from(!bytes.is_empty() && !bytes.ends_with(b"\n"));
+            Some((
+                abs_path.strip_prefix(root).ok()?.to_path_buf(),
+                newlines + unterminated,
+            ))
+        })
+        .collect()
+}
+
+/// Annotations added to entries of the `--tree` layout.
+#[derive(Debug, Default)]
+struct TreeAnnotations<'a> {
+    /// Line counts of content files; directories show their total.
+    line_counts: Option<&'a HashMap<PathBuf, usize>>,
+    /// Byte sizes of listed files, shown in an aligned column; directories show their total.
+    sizes: Option<&'a HashMap<PathBuf, u64>>,
+    /// Render each entry as a nested `- [ ]` Markdown list item.
+    checkbox: bool,
+}
+
+/// Renders nodes in the `--tree` layout, one entry per line.
+fn render_fs_tree(nodes: &[FsNode], annotations: &TreeAnnotations) -> String {
+    let mut rows = Vec::new();
+    let mut sizes = Vec::new();
+    collect_fs_lines(nodes, Path::new(""), 0, annotations, &mut rows, &mut sizes);
+    let lines: Vec<(String, Option<u64>)> = if annotations.checkbox {
+        rows.into_iter()
+            .map(|(depth, text)| format!("{}- [ ] {}", "  ".repeat(depth), text))
+            .zip(sizes)
+            .collect()
+    } else {
+        draw_tree(&rows).into_iter().zip(sizes).collect()
+    };
+    let width = lines
+        .iter()
+        .map(|(text, _)| text.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut output = String::new();
+    for (text, size) in lines {
+        let _ = match size.filter(|_| annotations.sizes.is_some()) {
+            Some(size) => writeln!(
+                output,
+                "{:<width$}  {:>6}",
+                text,
+                format_size(size),
+                width = width
+            ),
+            None => writeln!(output, "{}", text),
+        };
+    }
+    output
+}
+
+/// Pushes one `(depth, text)` row per node (and its descendants) and, in
+/// `sizes`, the node's size if known. Returns the line and byte totals below `nodes`.
+fn collect_fs_lines(
+    nodes: &[FsNode],
+    dir: &Path,
+    depth: usize,
+    annotations: &TreeAnnotations,
+    rows: &mut Vec<(usize, String)>,
+    sizes: &mut Vec<Option<u64>>,
+) -> (usize, u64) {
+    let (mut total_lines, mut total_size) = (0, 0);
+    for node in nodes {
+        let path = dir.join(&node.name);
+        // The node's own line goes before its children, so it is filled in last.
+        let index = rows.len();
+        rows.push((depth, String::new()));
+        sizes.push(None);
+        let (suffix, line_count, size) = if node.node_type == "directory" {
+            let (line_count, size) =
+                collect_fs_lines(&node.children, &path, depth + 1, annotations, rows, sizes);
+            let expanded = !node.collapsed && !node.ignored;
+            (
+                "/",
+                expanded.then_some(line_count),
+                expanded.then_some(size),
+            )
+        } else {
+            (
+                "",
+                annotations
+                    .line_counts
+                    .and_then(|counts| counts.get(&path).copied()),
+                annotations
+                    .sizes
+                    .and_then(|sizes| sizes.get(&path).copied()),
+            )
+        };
+        let label = if node.collapsed {
+            " [collapsed]"
+        } else if node.ignored {
+            " [ignored]"
+        } else {
+            ""
+        };
+        let count = match line_count.filter(|_| annotations.line_counts.is_some()) {
+            Some(lines) => format!(" ({})", lines),
+            None => String::new(),
+        };
+        rows[index].1 = format!("{}{}{}{}", node.name, suffix, count, label);
+        sizes[index] = size;
+        total_lines += line_count.unwrap_or(0);
+        total_size += size.unwrap_or(0);
+    }
+    (total_lines, total_size)
+}
+
+/// Formats a byte count for display, e.g. `512B` or `4.2K`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", size, UNITS[unit])
+}
+
+/// One line per file in `files` order, annotated with the `--sort` key.
+fn render_sorted_listing(
+    files: &[PathBuf],
+    root: &Path,
+    source: FileSource,
+    key: SortKey,
+) -> String {
+    let mut output = String::new();
+    for abs_path in files {
+        let relative_path = abs_path.strip_prefix(root).unwrap_or(abs_path);
+        let annotation = match key {
+            SortKey::Path => String::new(),
+            SortKey::Size => format!(" ({})", format_size(file_sort::size(abs_path, source))),
+            SortKey::Mtime => format!(" ({})", timestamp::utc(file_sort::modified(abs_path))),
+        };
+        let _ = writeln!(
+            output,
+            "{}{}",
+            relative_path.to_string_lossy().replace('\\', "/"),
+            annotation
+        );
+    }
+    output
+}
+
+fn render_tree_style(
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    order: EntryOrder,
+) -> String {
+    render_tree_with_markers(relative_files, tree_stubs, &HashMap::new(), order)
+}
+
+/// Like [`render_tree_style`], suffixing files that have an entry in `markers` with ` [X]`.
+fn render_tree_with_markers(
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    markers: &HashMap<&Path, char>,
+    order: EntryOrder,
+) -> String {
+    let stubs: HashMap<&Path, StubKind> = tree_stubs
+        .iter()
+        .map(|stub| (stub.path.as_path(), stub.kind))
+        .collect();
+    let is_dir = |path: &Path| stubs.get(path).is_some_and(|kind| kind.is_dir());
+    let mut entries: Vec<&PathBuf> = relative_files
+        .iter()
+        .chain(tree_stubs.iter().map(|stub| &stub.path))
+        .collect();
+    entries.sort_by(|a, b| order.compare_paths(a, is_dir(a), b, is_dir(b)));
+
+    let mut rows = Vec::new();
     let mut printed_dirs = HashSet::new();
-    for path in relative_files {
+    for path in entries {
         let mut current_path_builder = PathBuf::new();
         let components: Vec<Component> = path.components().collect();
 
         for (i, component) in components.iter().enumerate().take(components.len() - 1) {
             current_path_builder.push(component);
             if printed_dirs.insert(current_path_builder.clone()) {
-                let indent = "    ".repeat(i);
-                println!("{}{}/", indent, component.as_os_str().to_string_lossy());
+                rows.push((i, format!("{}/", component.as_os_str().to_string_lossy())));
             }
         }
         if let Some(file_name) = path.file_name() {
-            let indent = "    ".repeat(components.len().saturating_sub(1));
-            println!("{}{}", indent, file_name.to_string_lossy());
+            let depth = components.len().saturating_sub(1);
+            let file_name = file_name.to_string_lossy();
+            let row = match markers.get(path.as_path()) {
+                _ if let Some(kind) = stubs.get(path.as_path()) => format!(
+                    "{}{} [{}]",
+                    file_name,
+                    if kind.is_dir() { "/" } else { "" },
+                    kind.label()
+                ),
+                Some(marker) => format!("{} [{}]", file_name, marker),
+                None => file_name.into_owned(),
+            };
+            rows.push((depth, row));
+        }
+    }
+    draw_tree(&rows)
+        .into_iter()
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Prefixes each `(depth, text)` row, given in pre-order, with `tree`-style
+/// connectors: `├── ` or `└── ` for the entry and `│   ` for each ancestor
+/// that still has siblings below it.
+fn draw_tree(rows: &[(usize, String)]) -> Vec<String> {
+    // A row is the last of its siblings when no later row at the same depth
+    // appears before the walk climbs back above it.
+    let mut is_last = vec![false; rows.len()];
+    let mut sibling_below: Vec<bool> = Vec::new();
+    for (i, (depth, _)) in rows.iter().enumerate().rev() {
+        sibling_below.resize(depth + 1, false);
+        is_last[i] = !sibling_below[*depth];
+        sibling_below[*depth] = true;
+    }
+
+    let mut ancestor_last: Vec<bool> = Vec::new();
+    rows.iter()
+        .zip(is_last)
+        .map(|((depth, text), last)| {
+            ancestor_last.truncate(*depth);
+            let mut line: String = ancestor_last
+                .iter()
+                .map(|&done| if done { "    " } else { "│   " })
+                .collect();
+            line.push_str(if last { "└── " } else { "├── " });
+            line.push_str(text);
+            ancestor_last.push(last);
+            line
+        })
+        .collect()
+}
+
+/// Composes a review prompt: the tree with change markers, a `<changes>` section
+/// with the hunks changed since `rev`, and the full content of the changed files.
+fn render_review(
+    rev: &str,
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    root: &Path,
+    cli: &Cli,
+    options: &FormatOptions,
+) -> Result<String, GitRootError> {
+    let changes = git_diff::changed_hunks(
+        root,
+        rev,
+        &cli.include,
+        &cli.exclude,
+        glob_match::match_options(cli.case_insensitive),
+    )?;
+    let markers: HashMap<&Path, char> = changes
+        .iter()
+        .map(|file| (file.path.as_path(), file.marker()))
+        .collect();
+
+    // Deleted files are gone from the walk but still belong in the review tree.
+    let mut tree_files = relative_files.to_vec();
+    tree_files.extend(
+        changes
+            .iter()
+            .filter(|file| !relative_files.contains(&file.path))
+            .map(|file| file.path.clone()),
+    );
+    tree_files.sort();
+
+    let mut output = String::new();
+    let _ = writeln!(output, "<directory-structure>");
+    output.push_str(&render_tree_with_markers(
+        &tree_files,
+        tree_stubs,
+        &markers,
+        EntryOrder::new(cli.files_first),
+    ));
+    let _ = writeln!(output, "</directory-structure>");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "<changes>");
+    output.push_str(&git_diff::render_hunks(&changes, cli.quote_style));
+    let _ = writeln!(output, "</changes>");
+
+    let changed_files: Vec<PathBuf> = changes
+        .iter()
+        .filter(|file| file.status != "deleted")
+        .map(|file| root.join(&file.path))
+        .collect();
+    if !changed_files.is_empty() {
+        let _ = writeln!(output);
+    }
+    output.push_str(&get_file_contents(&changed_files, root, options)?);
+    Ok(output)
+}
+
+/// Environment variable naming the presets file when `--config` is not given.
+const CONFIG_ENV_VAR: &str = "CODE_CONTEXT_CONFIG";
+
+/// The presets file to load: `--config`, then `CODE_CONTEXT_CONFIG`, then
+/// the default location. An explicitly named file must exist; only the
+/// default may be missing.
+fn config_path(cli: &Cli) -> Result<Option<PathBuf>, GitRootError> {
+    let explicit = cli
+        .config
+        .clone()
+        .or_else(|| env::var_os(CONFIG_ENV_VAR).map(PathBuf::from));
+    match explicit {
+        Some(path) if !path.is_file() => Err(GitRootError::ConfigNotFound(path)),
+        Some(path) => Ok(Some(path)),
+        None => Ok(Config::default_path()),
+    }
+}
+
+/// Prints every preset and its patterns for `--list-presets`.
+fn list_presets(cli: &Cli) -> Result<(), GitRootError> {
+    let Some(path) = config_path(cli)?.filter(|path| path.is_file()) else {
+        let expected = Config::default_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "$XDG_CONFIG_HOME/rust-context/presets.toml".to_string());
+        println!(
+            "No presets file found. Create one at {} (see --config-schema).",
+            expected
+        );
+        return Ok(());
+    };
+    let config = Config::load(&path)?;
+    print!("{}", config.render_presets(&path)?);
+    Ok(())
+}
+
+/// Appends a preset's patterns to the command-line ones, skipping repeats,
+/// unless `replace` is set and the command line gave patterns for this list.
+fn add_preset_patterns(patterns: &mut Vec<String>, preset: &[String], replace: bool) {
+    if !replace || patterns.is_empty() {
+        *patterns = config::merge_patterns(patterns, preset);
+    }
+}
+
+/// Appends the selected preset's patterns to the CLI ones and records its name.
+///
+/// Case sensitivity comes from the CLI flags, then the preset, then the top level
+/// of the presets file.
+fn resolve_config(cli: &mut Cli, root: &Path) -> Result<(), GitRootError> {
+    let config = match config_path(cli)? {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    let selected = config.select_preset(cli.preset.as_deref(), root)?;
+    if !cli.case_insensitive && !cli.case_sensitive {
+        cli.case_insensitive = selected
+            .as_ref()
+            .and_then(|(_, preset)| preset.case_insensitive)
+            .or(config.case_insensitive)
+            .unwrap_or(false);
+    }
+    if let Some((name, preset)) = selected {
+        let replace = cli.replace;
+        add_preset_patterns(&mut cli.include, &preset.include, replace);
+        add_preset_patterns(&mut cli.exclude, &preset.exclude, replace);
+        add_preset_patterns(&mut cli.include_in_tree, &preset.include_in_tree, replace);
+        add_preset_patterns(&mut cli.tree_only, &preset.tree_only, replace);
+        if cli.max_file_size.is_none() {
+            cli.max_file_size = preset.max_file_size.map(|size| size.0);
+        }
+        if cli.template.is_none() {
+            cli.template = preset.template.clone();
         }
+        if cli.document_template.is_none() {
+            cli.document_template = preset.document_template.clone();
+        }
+        cli.active_preset = Some(name.to_string());
     }
+    Ok(())
+}
+
+/// Appends the globs listed in each `--exclude-file` to the excludes,
+/// skipping ones already present.
+fn load_exclude_files(cli: &mut Cli) -> Result<(), GitRootError> {
+    for path in &cli.exclude_file {
+        let contents =
+            fs::read_to_string(path).map_err(|e| GitRootError::FileRead(path.clone(), e))?;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !cli.exclude.iter().any(|pattern| pattern == line) {
+                cli.exclude.push(line.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites `\` to `/` in every glob, since paths are matched in their
+/// forward-slash form (so `src\**\*.rs` means `src/**/*.rs`).
+fn normalize_pattern_separators(cli: &mut Cli) {
+    for pattern in cli
+        .include
+        .iter_mut()
+        .chain(&mut cli.exclude)
+        .chain(&mut cli.include_in_tree)
+        .chain(&mut cli.tree_only)
+        .chain(&mut cli.force_include)
+        .chain(&mut cli.force_exclude)
+    {
+        if pattern.contains('\\') {
+            *pattern = pattern.replace('\\', "/");
+        }
+    }
+}
+
+/// Renders the `--git-info` line and the `--meta-header` provenance block, or
+/// nothing when neither is enabled.
+fn render_meta_header(cli: &Cli, root: &Path) -> String {
+    let q = cli.quote_style;
+    let mut output = String::new();
+    if cli.git_info
+        && cli.front_matter.is_none()
+        && let Some(info) = GitInfo::collect(root)
+    {
+        output.push_str(&info.render(q));
+    }
+    if !cli.meta_header {
+        return output;
+    }
+    let _ = write!(
+        output,
+        "<meta{}{}",
+        q.attr("tool", env!("CARGO_PKG_NAME")),
+        q.attr("version", env!("CARGO_PKG_VERSION"))
+    );
+    if cli.meta_timestamp {
+        output.push_str(&q.attr("generated-at", &timestamp::utc_now()));
+    }
+    if let Some(preset) = &cli.active_preset {
+        output.push_str(&q.attr("preset", preset));
+    }
+    let _ = writeln!(
+        output,
+        "{}>",
+        q.attr("root", &root.to_string_lossy().replace('\\', "/"))
+    );
+    for pattern in &cli.include {
+        let _ = writeln!(output, "<include{}/>", q.attr("pattern", pattern));
+    }
+    for pattern in &cli.include_in_tree {
+        let _ = writeln!(output, "<include-in-tree{}/>", q.attr("pattern", pattern));
+    }
+    for pattern in &cli.tree_only {
+        let _ = writeln!(output, "<tree-only{}/>", q.attr("pattern", pattern));
+    }
+    for pattern in &cli.exclude {
+        let _ = writeln!(output, "<exclude{}/>", q.attr("pattern", pattern));
+    }
+    output.push_str("</meta>\n\n");
+    output
+}
+
+/// Decides which listed files have their contents emitted.
+struct ContentSelector<'a> {
+    root: &'a Path,
+    /// Where sizes are taken from.
+    source: FileSource<'a>,
+    /// `None` when there are no include patterns.
+    includes: Option<IncludeSet>,
+    tree_only: Vec<Pattern>,
+    /// `--tree-only` patterns, which win over includes.
+    demoted: Vec<Pattern>,
+    min_file_size: u64,
+    max_file_size: Option<u64>,
+    filter: Option<FilterCommand>,
+    /// `--force-include` patterns, whose files always have their contents emitted.
+    forced: Vec<Pattern>,
+    /// With `--changed` or `--staged`, the only relative paths whose contents are emitted.
+    changed: Option<HashSet<PathBuf>>,
+    match_options: MatchOptions,
+}
+
+impl<'a> ContentSelector<'a> {
+    fn new(cli: &Cli, root: &'a Path, source: FileSource<'a>) -> Result<Self, GitRootError> {
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>, GitRootError> {
+            patterns
+                .iter()
+                .map(|s| Pattern::new(s).map_err(GitRootError::InvalidGlob))
+                .collect()
+        };
+        let match_options = glob_match::match_options(cli.case_insensitive);
+        Ok(ContentSelector {
+            root,
+            source,
+            includes: (!cli.include.is_empty())
+                .then(|| IncludeSet::new(&cli.include, match_options))
+                .transpose()?,
+            tree_only: compile(&cli.include_in_tree)?,
+            demoted: compile(&cli.tree_only)?,
+            min_file_size: cli.min_file_size,
+            max_file_size: cli.max_file_size,
+            filter: cli.filter_cmd.as_deref().and_then(|command| {
+                FilterCommand::parse(command, Duration::from_secs(cli.filter_cmd_timeout))
+            }),
+            forced: compile(&cli.force_include)?,
+            changed: match &cli.changed {
+                Some(rev) => Some(git_diff::changed_paths(root, rev)?),
+                None if cli.staged => Some(git_diff::staged_paths(root)?),
+                None => None,
+            },
+            match_options,
+        })
+    }
+
+    fn is_content(&self, abs_path: &Path) -> bool {
+        self.content_verdict(abs_path, &mut Trace::default())
+    }
+
+    /// Decides whether a listed file has its contents emitted (rather than
+    /// appearing in the tree only), recording every rule consulted in `trace`.
+    fn content_verdict(&self, abs_path: &Path, trace: &mut Trace) -> bool {
+        let Ok(rel_path) = abs_path.strip_prefix(self.root) else {
+            return false;
+        };
+        let rel_str = rel_path.to_string_lossy().replace('\\', "/");
+        if let Some(pattern) = first_match(&self.forced, &rel_str, self.match_options) {
+            trace.step("force-include", true, || describe_match(Some(pattern)));
+            return true;
+        }
+        if let Some(changed) = &self.changed {
+            let is_changed = changed.contains(rel_path);
+            trace.step("changed", !is_changed, || {
+                if is_changed { "changed" } else { "unchanged" }.to_string()
+            });
+            if !is_changed {
+                return false;
+            }
+        }
+        let matches_globs = match &self.includes {
+            Some(includes) => {
+                let included = includes.matches(&rel_str);
+                trace.step("include", included, || includes.describe(&rel_str));
+                let tree_only_by = first_match(&self.tree_only, &rel_str, self.match_options);
+                trace.step("include-in-tree", tree_only_by.is_some(), || {
+                    describe_match(tree_only_by)
+                });
+                included && tree_only_by.is_none()
+            }
+            // Without includes, --include-in-tree alone means a tree-only listing.
+            None => {
+                trace.step("include", false, || "no patterns".to_string());
+                if !self.tree_only.is_empty() {
+                    trace.step("include-in-tree", true, || {
+                        "given without --include, so every file is tree-only".to_string()
+                    });
+                }
+                self.tree_only.is_empty()
+            }
+        };
+        if !matches_globs {
+            return false;
+        }
+        if !self.demoted.is_empty() {
+            let demoted_by = first_match(&self.demoted, &rel_str, self.match_options);
+            trace.step("tree-only", demoted_by.is_some(), || {
+                describe_match(demoted_by)
+            });
+            if demoted_by.is_some() {
+                return false;
+            }
+        }
+        if self.min_file_size > 0 {
+            let size = self.source.size(abs_path).unwrap_or(0);
+            let too_small = size < self.min_file_size;
+            trace.step("min-file-size", too_small, || {
+                format!("{} bytes (minimum {})", size, self.min_file_size)
+            });
+            if too_small {
+                return false;
+            }
+        }
+        if let Some(limit) = self.max_file_size {
+            let size = self.source.size(abs_path).unwrap_or(0);
+            let too_large = size > limit;
+            trace.step("max-file-size", too_large, || {
+                format!("{} bytes (maximum {})", size, limit)
+            });
+            if too_large {
+                return false;
+            }
+        }
+        // The command only runs for files the globs already selected.
+        if let Some(filter) = &self.filter {
+            let accepted = filter.accepts(self.root, &rel_str);
+            trace.step("filter-cmd", !accepted, || {
+                if accepted { "accepted" } else { "rejected" }.to_string()
+            });
+            return accepted;
+        }
+        true
+    }
+}
+
+/// Picks the files whose contents are emitted, leaving tree-only matches out.
+fn select_content_files(
+    cli: &Cli,
+    root: &Path,
+    source: FileSource,
+    all_files: Vec<PathBuf>,
+) -> Result<Vec<PathBuf>, GitRootError> {
+    let selector = ContentSelector::new(cli, root, source)?;
+    Ok(all_files
+        .into_iter()
+        .filter(|abs_path| selector.is_content(abs_path))
+        .collect())
+}
+
+/// Renders the default output body: the JSON directory structure followed by file contents.
+fn render_bundle(
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    content_files: &[PathBuf],
+    root: &Path,
+    options: &FormatOptions,
+    order: EntryOrder,
+) -> String {
+    let contents = match get_file_contents(content_files, root, options) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Error processing file contents: {}", e);
+            String::new()
+        }
+    };
+    wrap_bundle(
+        relative_files,
+        tree_stubs,
+        !content_files.is_empty(),
+        &contents,
+        options,
+        order,
+    )
+}
+
+/// The directory structure followed by already rendered `contents`.
+fn wrap_bundle(
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    has_contents: bool,
+    contents: &str,
+    options: &FormatOptions,
+    order: EntryOrder,
+) -> String {
+    let mut output = String::new();
+    let tree = build_fs_tree(relative_files, tree_stubs, order);
+    match serde_json::to_string_pretty(&tree) {
+        Ok(json) => {
+            let _ = writeln!(output, "<directory-structure>");
+            let _ = writeln!(output, "{}", json);
+            let _ = writeln!(output, "</directory-structure>");
+        }
+        Err(e) => {
+            eprintln!("Error serializing JSON: {}", e);
+            // We can continue, to try and print file contents
+        }
+    }
+
+    if has_contents || options.always_emit_contents {
+        let _ = writeln!(output);
+    }
+    // With --always-emit-contents the blocks sit in a <file-contents> element
+    // that is present (possibly empty) in every bundle.
+    if options.always_emit_contents {
+        let _ = writeln!(output, "<file-contents>");
+    }
+    output.push_str(contents);
+    if options.always_emit_contents {
+        let _ = writeln!(output, "</file-contents>");
+    }
+    output
+}
+
+/// Bundle name used for files that sit directly in the repository root.
+const ROOT_BUNDLE: &str = "_root";
+
+fn bundle_name(relative_path: &Path) -> String {
+    let mut components = relative_path.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_string_lossy().into_owned(),
+        _ => ROOT_BUNDLE.to_string(),
+    }
+}
+
+/// Longest absolute path the platform accepts without special handling
+/// (`MAX_PATH` minus the terminator on Windows, `PATH_MAX` elsewhere).
+const MAX_PATH_LEN: usize = if cfg!(windows) { 259 } else { 4095 };
+
+/// Warns about, or with `strict` rejects, an output path over [`MAX_PATH_LEN`].
+fn check_path_length(path: &Path, strict: bool) -> Result<(), GitRootError> {
+    let full_path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let len = full_path.as_os_str().len();
+    if len <= MAX_PATH_LEN {
+        return Ok(());
+    }
+    if strict {
+        return Err(GitRootError::PathTooLong(full_path, len));
+    }
+    eprintln!(
+        "Warning: {} is {} characters, over the {}-character path limit; \
+         use a shorter --output-dir{}",
+        full_path.display(),
+        len,
+        MAX_PATH_LEN,
+        if cfg!(windows) {
+            " or the \\\\?\\ long-path prefix"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Writes one bundle per top-level directory (plus `_root`) into `output_dir`,
+/// each with its own tree and contents.
+fn write_split_bundles(
+    output_dir: &Path,
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    content_files: &[PathBuf],
+    root: &Path,
+    cli: &Cli,
+    options: &FormatOptions,
+) -> Result<Vec<PathBuf>, GitRootError> {
+    type Group = (Vec<PathBuf>, Vec<TreeStub>, Vec<PathBuf>);
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+    for path in relative_files {
+        groups
+            .entry(bundle_name(path))
+            .or_default()
+            .0
+            .push(path.clone());
+    }
+    // A stubbed top-level directory has no files but still gets its own bundle.
+    for stub in tree_stubs {
+        let name = match stub.path.components().count() {
+            1 if stub.kind.is_dir() => stub.path.to_string_lossy().into_owned(),
+            _ => bundle_name(&stub.path),
+        };
+        groups.entry(name).or_default().1.push(stub.clone());
+    }
+    for abs_path in content_files {
+        if let Ok(relative_path) = abs_path.strip_prefix(root) {
+            groups
+                .entry(bundle_name(relative_path))
+                .or_default()
+                .2
+                .push(abs_path.clone());
+        }
+    }
+
+    // Every path is checked before anything is written, so --strict-paths
+    // never leaves a partial set of bundles behind.
+    for name in groups.keys() {
+        check_path_length(&output_dir.join(format!("{}.txt", name)), cli.strict_paths)?;
+    }
+
+    fs::create_dir_all(output_dir)
+        .map_err(|e| GitRootError::FileWrite(output_dir.to_path_buf(), e))?;
+    let mut written = Vec::new();
+    for (name, (files, stubs, contents)) in groups {
+        let mut output = render_meta_header(cli, root);
+        output.push_str(&render_bundle(
+            &files,
+            &stubs,
+            &contents,
+            root,
+            options,
+            EntryOrder::new(cli.files_first),
+        ));
+        let path = output_dir.join(format!("{}.txt", name));
+        fs::write(&path, output).map_err(|e| GitRootError::FileWrite(path.clone(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Groups the rendered blocks into runs that stay under `budget` tokens once
+/// the per-chunk overhead (header, tree and license headers) is taken off;
+/// the first chunk also carries the read errors. A block over the budget on
+/// its own gets a chunk to itself, with a warning.
+fn plan_token_chunks(
+    rendered: &RenderedContents,
+    overhead: usize,
+    budget: usize,
+) -> Vec<Range<usize>> {
+    let available = budget.saturating_sub(overhead);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut current_tokens = tokens::estimate(&rendered.failures);
+    for (i, block) in rendered.blocks.iter().enumerate() {
+        let tokens = tokens::estimate(&block.text);
+        if tokens > available {
+            eprintln!(
+                "Warning: {} is about {} tokens, over the --split budget of {}; it gets a chunk of its own",
+                block.path,
+                tokens + overhead,
+                budget
+            );
+        }
+        if i > start && current_tokens + tokens > available {
+            chunks.push(start..i);
+            start = i;
+            current_tokens = 0;
+        }
+        current_tokens += tokens;
+    }
+    if start < rendered.blocks.len() || chunks.is_empty() {
+        chunks.push(start..rendered.blocks.len());
+    }
+    chunks
+}
+
+/// Renders the `--split` chunks, each a complete document with its own header
+/// and directory structure. Every file is read and rendered once, and chunks
+/// are sized from exactly the blocks they emit.
+fn render_token_chunks(
+    relative_files: &[PathBuf],
+    tree_stubs: &[TreeStub],
+    content_files: &[PathBuf],
+    root: &Path,
+    cli: &Cli,
+    options: &FormatOptions,
+    budget: usize,
+) -> Vec<String> {
+    let header = render_meta_header(cli, root);
+    let order = EntryOrder::new(cli.files_first);
+    let rendered = match render_contents(content_files, root, options) {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            eprintln!("Error processing file contents: {}", e);
+            RenderedContents::default()
+        }
+    };
+    let overhead = tokens::estimate(&header)
+        + tokens::estimate(&wrap_bundle(
+            relative_files,
+            tree_stubs,
+            false,
+            &rendered.licenses,
+            options,
+            order,
+        ));
+    plan_token_chunks(&rendered, overhead, budget)
+        .into_iter()
+        .enumerate()
+        .map(|(i, blocks)| {
+            let contents = rendered.assemble(&rendered.blocks[blocks.clone()], i == 0);
+            let mut output = header.clone();
+            output.push_str(&wrap_bundle(
+                relative_files,
+                tree_stubs,
+                !blocks.is_empty(),
+                &contents,
+                options,
+                order,
+            ));
+            output
+        })
+        .collect()
+}
+
+/// `FILE.N.ext` for the `n`th `--split` chunk of `--output FILE.ext`.
+fn chunk_path(output: &Path, n: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, n),
+    };
+    output.with_file_name(name)
+}
+
+/// Writes the `--split` chunks to numbered files next to `--output`, or to
+/// stdout as `<chunk>` sections when there is no output file.
+fn emit_token_chunks(cli: &Cli, chunks: &[String]) -> Result<(), GitRootError> {
+    let Some(output) = &cli.output else {
+        let q = cli.quote_style;
+        for (i, chunk) in chunks.iter().enumerate() {
+            print!(
+                "<chunk{}{}>\n{}</chunk>\n",
+                q.attr("index", &(i + 1).to_string()),
+                q.attr("of", &chunks.len().to_string()),
+                chunk
+            );
+        }
+        return Ok(());
+    };
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty())
+        && !parent.is_dir()
+    {
+        return Err(GitRootError::OutputDirMissing(parent.to_path_buf()));
+    }
+    for (i, chunk) in chunks.iter().enumerate() {
+        let path = chunk_path(output, i + 1);
+        fs::write(&path, chunk).map_err(|e| GitRootError::FileWrite(path.clone(), e))?;
+        if cli.checksum {
+            checksum::write_sidecar(&path)?;
+        }
+    }
+    eprintln!(
+        "Wrote {} chunk(s) to {} through {}",
+        chunks.len(),
+        chunk_path(output, 1).display(),
+        chunk_path(output, chunks.len()).display()
+    );
+    Ok(())
+}
+
+/// Writes the rendered output to `--output` (appending when `--append` is set) or stdout,
+/// wrapping it in a conversation first when `--conversation` is set.
+/// `last_output_hash` holds the hash of the output emitted by the previous
+/// `--watch` run, which `--on-change-only` compares against for every sink.
+fn emit_output(
+    cli: &Cli,
+    output: &str,
+    last_output_hash: &mut Option<String>,
+) -> Result<(), GitRootError> {
+    let wrapped;
+    let output = match cli.conversation {
+        Some(format) => {
+            wrapped = conversation::wrap(format, cli.system.as_deref(), output)?;
+            wrapped.as_str()
+        }
+        None => output,
+    };
+    if cli.on_change_only {
+        let hash = baseline::content_hash(output.as_bytes());
+        let unchanged = last_output_hash.as_deref() == Some(hash.as_str())
+            || cli.output.as_deref().is_some_and(|path| {
+                fs::read(path).is_ok_and(|existing| existing == output.as_bytes())
+            });
+        if unchanged {
+            eprintln!("Output is unchanged, not emitting it again");
+            return Ok(());
+        }
+        *last_output_hash = Some(hash);
+    }
+    if cli.clipboard {
+        match clipboard::copy_to_clipboard(output) {
+            Ok(()) => {
+                eprintln!("Copied {} bytes to the clipboard", output.len());
+                return Ok(());
+            }
+            Err(e) => eprintln!("Warning: {}, printing to stdout instead", e),
+        }
+    }
+    let Some(path) = &cli.output else {
+        print!("{}", output);
+        return Ok(());
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty())
+        && !parent.is_dir()
+    {
+        return Err(GitRootError::OutputDirMissing(parent.to_path_buf()));
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(cli.append)
+        .truncate(!cli.append)
+        .open(path)
+        .map_err(|e| GitRootError::FileWrite(path.clone(), e))?;
+    file.write_all(output.as_bytes())
+        .map_err(|e| GitRootError::FileWrite(path.clone(), e))?;
+    if cli.checksum {
+        // Covers the whole file, including earlier --append updates.
+        checksum::write_sidecar(path)?;
+    }
+    Ok(())
+}
+
+/// Renders only the files that changed since `previous` as a timestamped `<update>` block.
+fn render_update_block(
+    current: &Baseline,
+    previous: &Baseline,
+    content_files: &[PathBuf],
+    root: &Path,
+    options: &FormatOptions,
+) -> Result<String, GitRootError> {
+    let changed: HashSet<&str> = current.changed_since(previous).into_iter().collect();
+    let removed = current.removed_since(previous);
+    if changed.is_empty() && removed.is_empty() {
+        return Ok(String::new());
+    }
+
+    let changed_files: Vec<PathBuf> = content_files
+        .iter()
+        .filter(|abs_path| {
+            abs_path
+                .strip_prefix(root)
+                .map(|rel| changed.contains(rel.to_string_lossy().replace('\\', "/").as_str()))
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect();
+
+    let mut output = format!(
+        "\n<update{}>\n",
+        options.quote_style.attr("at", &timestamp::utc_now())
+    );
+    output.push_str(&get_file_contents(&changed_files, root, options)?);
+    for path in removed {
+        let _ = writeln!(
+            output,
+            "<removed{}/>",
+            options.quote_style.attr("src", path)
+        );
+    }
+    output.push_str("</update>\n");
+    Ok(output)
+}
+
+/// Exit status when `--partial-on-error` skipped files but still produced output.
+const PARTIAL_OUTPUT_EXIT_CODE: i32 = 3;
+
+/// Reports the files `--partial-on-error` skipped and exits with
+/// [`PARTIAL_OUTPUT_EXIT_CODE`], except under `--watch`, which keeps going.
+fn exit_if_partial(cli: &Cli, read_failures: Option<&ReadFailures>) {
+    if let Some(failures) = read_failures
+        && !failures.is_empty()
+    {
+        failures.report();
+        eprintln!(
+            "Warning: {} file(s) could not be read, output is partial",
+            failures.len()
+        );
+        if !cli.watch {
+            process::exit(PARTIAL_OUTPUT_EXIT_CODE);
+        }
+    }
+}
+
+/// Whether `abs_path` is a file this program writes: the `--output` file, its
+/// `--split` chunks and `--append` baseline, or anything in `--output-dir`.
+fn is_own_output(cli: &Cli, abs_path: &Path) -> bool {
+    let resolve = |path: &Path| {
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::canonicalize(parent)
+            .unwrap_or_else(|_| parent.to_path_buf())
+            .join(path.file_name().unwrap_or_default())
+    };
+    let in_output_dir = cli
+        .output_dir
+        .as_deref()
+        .is_some_and(|dir| abs_path.starts_with(resolve(dir)));
+    let next_to_output = cli.output.as_deref().map(resolve).is_some_and(|output| {
+        let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+        abs_path.parent() == output.parent()
+            && abs_path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(stem.as_ref()))
+    });
+    in_output_dir || next_to_output
+}
+
+/// `--watch`: generates the output, then again whenever a file the walk
+/// lists is added, changed or removed. Changes to excluded or ignored files,
+/// to `.git` and to the output itself are not acted on.
+fn watch_and_generate(
+    cli: &Cli,
+    root: &Path,
+    output_template: Option<&OutputTemplate>,
+    line_ranges: Option<&LineRanges>,
+) -> Result<(), GitRootError> {
+    let all_include_patterns = [cli.include.as_slice(), cli.include_in_tree.as_slice()].concat();
+    let extra_ignore_rules = cli
+        .exclude_by_gitignore_of
+        .as_deref()
+        .map(load_external_ignore_rules)
+        .transpose()?;
+    let walk_options = walk_options(
+        cli,
+        &all_include_patterns,
+        extra_ignore_rules.as_deref(),
+        Progress::default(),
+    );
+    let is_watched = |abs_path: &Path| {
+        let Ok(relative_path) = abs_path.strip_prefix(root) else {
+            return false;
+        };
+        !relative_path.starts_with(".git")
+            && !is_own_output(cli, abs_path)
+            && FileWalk::new(root, &walk_options).is_ok_and(|walk| walk.lists_file(relative_path))
+    };
+    let mut last_output_hash = None;
+    watch::run(root, is_watched, || {
+        generate(
+            cli,
+            root,
+            output_template,
+            line_ranges,
+            &mut last_output_hash,
+        )
+    })
 }
 
 fn main() {
-    let cli = Cli::parse();
+    clipboard::serve_if_holder();
+    let mut cli = Cli::parse();
+    if cli.config_schema {
+        print!("{}", config::SCHEMA);
+        return;
+    }
+    if cli.strip_comments_languages {
+        print!("{}", comments::render_supported());
+        return;
+    }
+    if cli.list_presets {
+        if let Err(e) = list_presets(&cli) {
+            eprintln!("Error loading presets: {}", e);
+        }
+        return;
+    }
+    // "-" lets scripts always pass --output and still get stdout.
+    if cli.output.as_deref() == Some(Path::new("-")) {
+        cli.output = None;
+    }
 
-    let root = match find_git_root() {
+    open_files::set_max_open_files(
+        cli.max_open_files
+            .unwrap_or_else(open_files::default_max_open_files),
+    );
+
+    let root = match find_git_root(cli.path.as_deref().unwrap_or(Path::new("."))) {
         Ok(path) => path,
         Err(err) => {
             eprintln!("{}", err);
@@ -250,98 +2585,659 @@ fn main() {
         }
     };
 
+    if let Err(err) = resolve_config(&mut cli, &root) {
+        eprintln!("Error loading presets: {}", err);
+        return;
+    }
+    if let Err(err) = load_exclude_files(&mut cli) {
+        eprintln!("Error loading exclude patterns: {}", err);
+        return;
+    }
+    let output_template =
+        match OutputTemplate::parse(cli.template.as_deref(), cli.document_template.as_deref()) {
+            Ok(output_template) => output_template,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return;
+            }
+        };
+    if !cli.literal_backslash {
+        normalize_pattern_separators(&mut cli);
+    }
+    let match_options = glob_match::match_options(cli.case_insensitive);
+    let line_ranges = match LineRanges::extract(&mut cli.include, match_options) {
+        Ok(line_ranges) => line_ranges,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+
+    if cli.watch {
+        let result =
+            watch_and_generate(&cli, &root, output_template.as_ref(), line_ranges.as_ref());
+        if let Err(e) = result {
+            eprintln!("Error watching files: {}", e);
+        }
+        return;
+    }
+    generate(
+        &cli,
+        &root,
+        output_template.as_ref(),
+        line_ranges.as_ref(),
+        &mut None,
+    );
+}
+
+/// Walks the repository and writes the output once, for a command line whose
+/// presets and patterns are already resolved. `--watch` calls it again after
+/// every change, passing the hash of the output it last emitted.
+fn generate(
+    cli: &Cli,
+    root: &Path,
+    output_template: Option<&OutputTemplate>,
+    line_ranges: Option<&LineRanges>,
+    last_output_hash: &mut Option<String>,
+) {
+    let match_options = glob_match::match_options(cli.case_insensitive);
+    let mut redactor = match cli.redact_from.as_deref().map(Redactor::from_file) {
+        Some(Ok(redactor)) => Some(redactor),
+        Some(Err(err)) => {
+            eprintln!("Error loading redaction patterns: {}", err);
+            return;
+        }
+        None => None,
+    };
+    if cli.redact {
+        redactor
+            .get_or_insert_with(Redactor::default)
+            .add_secret_patterns();
+    }
+
+    if let Some(rev) = &cli.diff_context_only {
+        let files =
+            match git_diff::changed_hunks(root, rev, &cli.include, &cli.exclude, match_options) {
+                Ok(files) => files,
+                Err(err) => {
+                    eprintln!("Error collecting diff hunks: {}", err);
+                    return;
+                }
+            };
+        let touched: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
+        let mut output = render_meta_header(cli, root);
+        match serde_json::to_string_pretty(&build_fs_tree(
+            &touched,
+            &[],
+            EntryOrder::new(cli.files_first),
+        )) {
+            Ok(json) => {
+                let _ = writeln!(output, "<directory-structure>");
+                let _ = writeln!(output, "{}", json);
+                let _ = writeln!(output, "</directory-structure>");
+            }
+            Err(e) => eprintln!("Error serializing JSON: {}", e),
+        }
+        if !files.is_empty() {
+            let _ = writeln!(output);
+        }
+        output.push_str(&git_diff::render_hunks(&files, cli.quote_style));
+        if let Err(e) = emit_output(cli, &output, last_output_hash) {
+            eprintln!("Error writing output: {}", e);
+        }
+        return;
+    }
+
     let all_include_patterns = [cli.include.as_slice(), cli.include_in_tree.as_slice()].concat();
 
-    let all_files = match list_non_ignored_files(&root, &all_include_patterns, &cli.exclude) {
-        Ok(files) => files,
+    let extra_ignore_rules = match cli.exclude_by_gitignore_of.as_deref() {
+        Some(source) => match load_external_ignore_rules(source) {
+            Ok(rules) => Some(rules),
+            Err(err) => {
+                eprintln!("Error loading ignore rules: {}", err);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let progress = Progress::new(cli.progress);
+    let walk_options = walk_options(
+        cli,
+        &all_include_patterns,
+        extra_ignore_rules.as_deref(),
+        progress.clone(),
+    );
+    let aliases = if cli.dereference_aliases {
+        AliasResolver::load(root)
+    } else {
+        None
+    };
+
+    let attributes = GitAttributes::open(root);
+
+    let snapshot = match cli
+        .at_ref
+        .as_deref()
+        .map(|rev| RefSnapshot::open(root, rev))
+    {
+        Some(Ok(snapshot)) => Some(snapshot),
+        Some(Err(err)) => {
+            eprintln!("Error reading ref: {}", err);
+            return;
+        }
+        None => None,
+    };
+
+    let source = FileSource::new(snapshot.as_ref());
+    if snapshot.is_some() && cli.sort == SortKey::Mtime {
+        eprintln!("Error: --sort mtime reads the working tree and cannot be used with --at-ref");
+        return;
+    }
+
+    // Redaction counts and ref snapshots need every file read, so they bypass the cache.
+    let content_cache = (!cli.no_content_cache && redactor.is_none() && snapshot.is_none())
+        .then(ContentCache::default_dir)
+        .flatten()
+        .map(|dir| {
+            let fingerprint = format!(
+                "{}\0{}\0{:?}\0{:?}\0{}\0{}\0{}\0{:?}\0{}",
+                cli.signatures_only,
+                cli.strip_comments,
+                cli.encoding,
+                cli.binary_detect,
+                cli.binary_scan_bytes,
+                cli.include_binary,
+                cli.lossy_utf8,
+                aliases,
+                fs::read_to_string(root.join(".gitattributes")).unwrap_or_default()
+            );
+            ContentCache::new(dir, &fingerprint)
+        });
+
+    let read_failures = cli.partial_on_error.then(ReadFailures::default);
+    let format_options = FormatOptions {
+        redactor: redactor.as_ref(),
+        signatures_only: cli.signatures_only,
+        strip_comments: cli.strip_comments,
+        quote_style: cli.quote_style,
+        aliases: aliases.as_ref(),
+        encoding: cli.encoding,
+        fold_licenses: cli.fold_licenses,
+        binary: BinaryDetector {
+            mode: cli.binary_detect,
+            scan_bytes: cli.binary_scan_bytes,
+            attributes: attributes.as_ref(),
+        },
+        lossy_utf8: cli.lossy_utf8,
+        include_binary: cli.include_binary,
+        binary_base64: cli.binary_base64,
+        max_tokens: cli.max_tokens,
+        line_numbers: cli.line_numbers,
+        max_lines: cli.max_lines,
+        head: cli.head,
+        line_ranges,
+        anchor_interval: cli.anchor_interval.map(NonZeroUsize::get),
+        annotate: cli.annotate,
+        cdata_extensions: &cli.cdata_for,
+        source,
+        notes: &cli.note,
+        match_options,
+        dedup_symlinks: cli.follow_symlinks,
+        content_cache: content_cache.as_ref(),
+        always_emit_contents: cli.always_emit_contents,
+        aggregate_below: cli.aggregate_small_files,
+        read_failures: read_failures.as_ref(),
+        progress: progress.clone(),
+    };
+
+    if cli.explain.is_some() || cli.explain_all {
+        let report = FileWalk::new(root, &walk_options).and_then(|walk| {
+            let selector = ContentSelector::new(cli, root, source)?;
+            let explainer = explain::Explainer::new(root, walk, selector, &format_options);
+            match &cli.explain {
+                Some(path) => {
+                    let relative_path = path.strip_prefix(root).unwrap_or(path);
+                    Ok(explain::render_steps(&explainer.decide(relative_path)?))
+                }
+                None => Ok(explain::render_table(&explainer.decide_all()?, cli.tsv)),
+            }
+        });
+        match report {
+            Ok(report) => print!("{}", report),
+            Err(e) => eprintln!("Error explaining filters: {}", e),
+        }
+        return;
+    }
+
+    if cli.json_stream {
+        // Records are written as the walk finds files, without listing the tree first.
+        let result = ContentSelector::new(cli, root, source).and_then(|selector| {
+            let files = FileWalk::new(root, &walk_options)?.filter(|file| {
+                file.as_ref()
+                    .map_or(true, |abs_path| selector.is_content(abs_path))
+            });
+            stream_file_records(files, root, &format_options, &mut std::io::stdout().lock())
+        });
+        if let Err(e) = result {
+            eprintln!("Error streaming file records: {}", e);
+        }
+        if let Some(redactor) = &redactor {
+            redactor.report();
+        }
+        exit_if_partial(cli, read_failures.as_ref());
+        return;
+    }
+
+    let FileListing {
+        files: mut all_files,
+        tree_stubs,
+    } = match snapshot.as_ref().map_or_else(
+        || list_non_ignored_files(root, &walk_options),
+        |snapshot| {
+            Ok(FileListing {
+                files: snapshot.list_files(
+                    walk_options.includes,
+                    walk_options.excludes,
+                    walk_options.force_includes,
+                    walk_options.force_excludes,
+                    walk_options.match_options,
+                )?,
+                tree_stubs: Vec::new(),
+            })
+        },
+    ) {
+        Ok(listing) => listing,
         Err(err) => {
             eprintln!("Error listing files: {}", err);
             return;
         }
     };
 
-    let mut relative_files: Vec<PathBuf> = all_files
+    // By default contents follow the tree: natural order, directories apart from files.
+    let order = EntryOrder::new(cli.files_first);
+    file_sort::sort_files(&mut all_files, cli.sort, order, cli.reverse, source);
+    let relative_files: Vec<PathBuf> = all_files
         .iter()
-        .filter_map(|abs_path| abs_path.strip_prefix(&root).ok())
+        .filter_map(|abs_path| abs_path.strip_prefix(root).ok())
         .map(|rel_path| rel_path.to_path_buf())
         .collect();
-    relative_files.sort();
 
-    if cli.tree {
-        print_tree_style(&relative_files);
+    let mut output = String::new();
+
+    if let Some(rev) = &cli.review {
+        output.push_str(&render_meta_header(cli, root));
+        match render_review(
+            rev,
+            &relative_files,
+            &tree_stubs,
+            root,
+            cli,
+            &format_options,
+        ) {
+            Ok(review) => output.push_str(&review),
+            Err(e) => {
+                eprintln!("Error building review: {}", e);
+                return;
+            }
+        }
+    } else if cli.tree && cli.sort != SortKey::Path {
+        // A size or time order cuts across directories, so it is shown flat.
+        output.push_str(&render_sorted_listing(&all_files, root, source, cli.sort));
+    } else if cli.tree
+        && (cli.compact_tree
+            || cli.reverse
+            || cli.sort_dirs_by_size
+            || cli.tree_lines
+            || cli.show_sizes
+            || cli.tree_checkbox_markdown)
+    {
+        let content_files = if cli.sort_dirs_by_size || cli.tree_lines {
+            match select_content_files(cli, root, source, all_files) {
+                Ok(content_files) => content_files,
+                Err(e) => {
+                    eprintln!("Error filtering content files: {}", e);
+                    return;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let tree = arranged_fs_tree(
+            cli,
+            root,
+            source,
+            &relative_files,
+            &tree_stubs,
+            &content_files,
+        );
+        let line_counts = cli
+            .tree_lines
+            .then(|| count_lines(&content_files, root, source, &format_options.binary));
+        let sizes = cli.show_sizes.then(|| {
+            relative_files
+                .iter()
+                .filter_map(|path| Some((path.clone(), source.size(&root.join(path))?)))
+                .collect::<HashMap<_, _>>()
+        });
+        output.push_str(&render_fs_tree(
+            &tree,
+            &TreeAnnotations {
+                line_counts: line_counts.as_ref(),
+                sizes: sizes.as_ref(),
+                checkbox: cli.tree_checkbox_markdown,
+            },
+        ));
+    } else if cli.tree {
+        output.push_str(&render_tree_style(&relative_files, &tree_stubs, order));
+    } else if cli.stats {
+        match select_content_files(cli, root, source, all_files) {
+            Ok(content_files) => output.push_str(
+                &ScanStats::collect(&relative_files, &content_files, root, source).render(),
+            ),
+            Err(e) => {
+                eprintln!("Error filtering content files: {}", e);
+                return;
+            }
+        }
+    } else if cli.language_stats {
+        match select_content_files(cli, root, source, all_files) {
+            Ok(content_files) => output.push_str(&languages::render_language_stats(
+                &languages::language_stats(&content_files, source),
+            )),
+            Err(e) => {
+                eprintln!("Error filtering content files: {}", e);
+                return;
+            }
+        }
     } else if cli.json {
-        let tree = build_fs_tree(&relative_files);
+        let content_files = if cli.sort_dirs_by_size {
+            match select_content_files(cli, root, source, all_files) {
+                Ok(content_files) => content_files,
+                Err(e) => {
+                    eprintln!("Error filtering content files: {}", e);
+                    return;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let tree = arranged_fs_tree(
+            cli,
+            root,
+            source,
+            &relative_files,
+            &tree_stubs,
+            &content_files,
+        );
         match serde_json::to_string_pretty(&tree) {
-            Ok(json) => println!("{}", json),
+            Ok(json) => {
+                let _ = writeln!(output, "{}", json);
+            }
             Err(e) => eprintln!("Error serializing JSON: {}", e),
         }
-    } else {
-        let tree = build_fs_tree(&relative_files);
+    } else if cli.tree_json {
+        let content_files = match select_content_files(cli, root, source, all_files) {
+            Ok(content_files) => content_files,
+            Err(e) => {
+                eprintln!("Error filtering content files: {}", e);
+                return;
+            }
+        };
+        let content_set: HashSet<&Path> = content_files
+            .iter()
+            .filter_map(|abs_path| abs_path.strip_prefix(root).ok())
+            .collect();
+        let tree = tree_json::build_tree_json(&relative_files, &content_set);
         match serde_json::to_string_pretty(&tree) {
             Ok(json) => {
-                println!("<directory-structure>");
-                println!("{}", json);
-                println!("</directory-structure>");
+                let _ = writeln!(output, "{}", json);
             }
+            Err(e) => eprintln!("Error serializing JSON: {}", e),
+        }
+    } else {
+        let content_files_result = select_content_files(cli, root, source, all_files);
+
+        let content_files = match content_files_result {
+            Ok(content_files) => content_files,
             Err(e) => {
-                eprintln!("Error serializing JSON: {}", e);
-                // We can continue, to try and print file contents
+                eprintln!("Error filtering content files: {}", e);
+                return;
+            }
+        };
+
+        if let Some(output_dir) = cli.output_dir.as_deref().filter(|_| cli.split_by_dir) {
+            match write_split_bundles(
+                output_dir,
+                &relative_files,
+                &tree_stubs,
+                &content_files,
+                root,
+                cli,
+                &format_options,
+            ) {
+                Ok(written) => eprintln!(
+                    "Wrote {} bundle(s) to {}",
+                    written.len(),
+                    output_dir.display()
+                ),
+                Err(e) => eprintln!("Error writing bundles: {}", e),
+            }
+            if let Some(redactor) = &redactor {
+                redactor.report();
             }
+            return;
         }
 
-        let content_files_result: Result<Vec<PathBuf>, GitRootError> = (|| {
-            if cli.include.is_empty() {
-                if cli.include_in_tree.is_empty() {
-                    Ok(all_files)
-                } else {
-                    Ok(Vec::new())
-                }
-            } else {
-                let include_patterns: Result<Vec<Pattern>, _> =
-                    cli.include.iter().map(|s| Pattern::new(s)).collect();
-                let include_patterns = include_patterns.map_err(GitRootError::InvalidGlob)?;
+        if let Some(budget) = cli.split {
+            let chunks = render_token_chunks(
+                &relative_files,
+                &tree_stubs,
+                &content_files,
+                root,
+                cli,
+                &format_options,
+                budget as usize,
+            );
+            if let Err(e) = emit_token_chunks(cli, &chunks) {
+                eprintln!("Error writing chunks: {}", e);
+            }
+            if let Some(redactor) = &redactor {
+                redactor.report();
+            }
+            exit_if_partial(cli, read_failures.as_ref());
+            return;
+        }
 
-                let tree_only_patterns: Result<Vec<Pattern>, _> = cli
-                    .include_in_tree
-                    .iter()
-                    .map(|s| Pattern::new(s))
-                    .collect();
-                let tree_only_patterns = tree_only_patterns.map_err(GitRootError::InvalidGlob)?;
-
-                let filtered = all_files
-                    .into_iter()
-                    .filter(|abs_path| {
-                        if let Ok(rel_path) = abs_path.strip_prefix(&root) {
-                            let rel_str = rel_path.to_string_lossy().replace('\\', "/");
-
-                            let matches_include =
-                                include_patterns.iter().any(|p| p.matches(&rel_str));
-                            let matches_tree_only =
-                                tree_only_patterns.iter().any(|p| p.matches(&rel_str));
-
-                            matches_include && !matches_tree_only
-                        } else {
-                            false
-                        }
-                    })
-                    .collect();
-                Ok(filtered)
+        // In append mode, a previous baseline means only the delta is written.
+        let baseline_path = cli.output.as_deref().map(Baseline::sidecar_path);
+        let mut appended_update = false;
+        if let Some(baseline_path) = baseline_path.as_deref().filter(|_| cli.append) {
+            let result = Baseline::capture(&content_files, root).and_then(|current| {
+                let update = match Baseline::load(baseline_path)? {
+                    Some(previous) => Some(render_update_block(
+                        &current,
+                        &previous,
+                        &content_files,
+                        root,
+                        &format_options,
+                    )?),
+                    None => None,
+                };
+                current.save(baseline_path)?;
+                Ok(update)
+            });
+            match result {
+                Ok(Some(update)) => {
+                    output.push_str(&update);
+                    appended_update = true;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("Error updating baseline: {}", e);
+                    return;
+                }
             }
-        })();
+        }
 
-        match content_files_result {
-            Ok(content_files) => {
-                if !content_files.is_empty() {
-                    println!();
+        if let Some(output_template) = &output_template {
+            let files = read_document_files(&relative_files, &content_files, root, &format_options);
+            output.push_str(&output_template.render(
+                &render_tree_style(&relative_files, &tree_stubs, order),
+                &files,
+            ));
+        } else if let Some(format) = cli.format {
+            let files = read_document_files(&relative_files, &content_files, root, &format_options);
+            match markup::render(
+                format,
+                &render_tree_style(&relative_files, &tree_stubs, order),
+                &files,
+                cli.toc,
+            ) {
+                Ok(document) => output.push_str(&document),
+                Err(e) => {
+                    eprintln!("Error rendering document: {}", e);
+                    return;
                 }
-                match get_file_contents(&content_files, &root) {
-                    Ok(output) => print!("{}", output), // ‼️ print! not println!
-                    Err(e) => eprintln!("Error processing file contents: {}", e),
+            }
+        } else if !appended_update {
+            output.push_str(&render_meta_header(cli, root));
+            if let Some(rev) = &cli.diff_stat {
+                match git_diff::diff_stat(root, rev, &cli.include, &cli.exclude, match_options) {
+                    Ok(stats) => {
+                        output.push_str(&git_diff::render_diff_stat(&stats, cli.quote_style));
+                        let _ = writeln!(output);
+                    }
+                    Err(e) => {
+                        eprintln!("Error computing diff stat: {}", e);
+                        return;
+                    }
                 }
             }
-            Err(e) => {
-                eprintln!("Error filtering content files: {}", e);
+            output.push_str(&render_bundle(
+                &relative_files,
+                &tree_stubs,
+                &content_files,
+                root,
+                &format_options,
+                order,
+            ));
+            if cli.summary {
+                let _ = writeln!(output);
+                output.push_str(
+                    &ScanStats::collect(&relative_files, &content_files, root, source)
+                        .render_summary(cli.quote_style),
+                );
+            }
+        }
+
+        if !cli.no_overflow_warning {
+            let guard = OverflowGuard {
+                max_files: cli.overflow_warn_files,
+                max_tokens: cli.overflow_warn_tokens,
+            };
+            if let Some(warning) = guard.check(&output, &content_files, root, source) {
+                eprint!("{}", warning);
+            }
+        }
+
+        if let Some(redactor) = &redactor {
+            redactor.report();
+        }
+    }
+
+    if let Some(format) = cli.front_matter {
+        let git_info = cli.git_info.then(|| GitInfo::collect(root)).flatten();
+        let front_matter = FrontMatter {
+            root: root.to_string_lossy().replace('\\', "/"),
+            preset: cli.active_preset.as_deref(),
+            files: relative_files.len(),
+            tokens: tokens::estimate(&output),
+            include: &cli.include,
+            include_in_tree: &cli.include_in_tree,
+            tree_only: &cli.tree_only,
+            exclude: &cli.exclude,
+            git: git_info.as_ref(),
+            generated_at: cli.front_matter_timestamp.then(timestamp::utc_now),
+        };
+        output.insert_str(0, &front_matter.render(format));
+    }
+
+    if let Err(e) = emit_output(cli, &output, last_output_hash) {
+        eprintln!("Error writing output: {}", e);
+    }
+    exit_if_partial(cli, read_failures.as_ref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh git repository under the temp directory, removed when dropped.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let root =
+                env::temp_dir().join(format!("rust-context-unit-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).expect("create fixture root");
+            Repository::init(&root).expect("init fixture repo");
+            for (path, contents) in files {
+                let path = root.join(path);
+                fs::create_dir_all(path.parent().expect("parent")).expect("create parent");
+                fs::write(path, contents).expect("write fixture file");
             }
+            Fixture(root)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
         }
     }
+
+    #[test]
+    fn file_walk_yields_listed_files_lazily() {
+        let fixture = Fixture::new(
+            "file-walk",
+            &[
+                (".gitignore", "target/\n"),
+                ("src/main.rs", ""),
+                ("src/lib.rs", ""),
+                ("target/out.rs", ""),
+                ("notes.log", ""),
+            ],
+        );
+        let excludes = ["*.log".to_string()];
+        let options = WalkOptions {
+            excludes: &excludes,
+            gitignore: true,
+            ..WalkOptions::default()
+        };
+
+        let mut walk = FileWalk::new(&fixture.0, &options).expect("start walk");
+        let first = walk.next().expect("a listed file").expect("readable entry");
+        assert!(first.starts_with(&fixture.0), "{}", first.display());
+
+        let mut files: Vec<PathBuf> = std::iter::once(Ok(first))
+            .chain(walk)
+            .map(|path| {
+                let path = path.expect("readable entry");
+                path.strip_prefix(&fixture.0)
+                    .expect("under root")
+                    .to_path_buf()
+            })
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                PathBuf::from(".gitignore"),
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("src/main.rs"),
+            ]
+        );
+    }
 }
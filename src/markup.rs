@@ -0,0 +1,242 @@
+use crate::GitRootError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Document formats that replace the XML bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MarkupFormat {
+    /// Org-mode headings with `#+begin_src` blocks.
+    Org,
+    /// Typst headings with fenced raw blocks.
+    Typst,
+    /// Markdown headings with fenced code blocks.
+    Markdown,
+    /// A JSON object with the tree as a string and one entry per listed file.
+    Json,
+}
+
+/// A listed file as handed to a document format.
+#[derive(Debug, Serialize)]
+pub struct DocumentFile {
+    pub path: String,
+    /// The code-block tag for the file's extension, when it has a known language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<&'static str>,
+    /// Whether the file's contents were selected, as opposed to listed in the tree only.
+    pub included: bool,
+    /// `None` for binary, tree-only and unreadable files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+    /// A binary file's bytes under `--binary-base64`, wrapped like in the XML bundle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DocumentFile {
+    /// The language tag and text of the file's code block: its contents, or
+    /// a binary file's base64 encoding.
+    pub fn block(&self) -> Option<(Option<&'static str>, &str)> {
+        match (&self.content, &self.content_base64) {
+            (Some(content), _) => Some((self.language, content)),
+            (None, Some(encoded)) => Some((Some("base64"), encoded)),
+            (None, None) => None,
+        }
+    }
+
+    /// The text printed in place of contents that are not emitted.
+    pub fn placeholder(&self) -> String {
+        match &self.error {
+            Some(error) => format!("Could not read file: {}\n", error),
+            None => "Binary file, contents omitted.\n".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonDocument<'a> {
+    tree: &'a str,
+    files: &'a [DocumentFile],
+}
+
+/// Org treats `*` and `#+` at the start of a line as markup even inside a
+/// block, so such lines get the standard `,` escape.
+fn escape_org_block(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            if line.starts_with('*') || line.starts_with("#+") || line.starts_with(',') {
+                format!(",{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+/// A backtick fence longer than any backtick run inside `content`.
+fn backtick_fence(content: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat((longest_run + 1).max(3))
+}
+
+fn ensure_newline(content: &str) -> &str {
+    if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    }
+}
+
+/// Heading anchors as GitHub generates them: lowercased, punctuation other
+/// than `-` and `_` dropped, spaces turned into `-`, and `-1`, `-2`, ...
+/// appended to repeats.
+#[derive(Debug, Default)]
+struct Slugger {
+    seen: HashMap<String, usize>,
+}
+
+impl Slugger {
+    fn slug(&mut self, heading: &str) -> String {
+        let base: String = heading
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                '-' | '_' => Some(c),
+                c if c.is_alphanumeric() => Some(c),
+                _ => None,
+            })
+            .flat_map(char::to_lowercase)
+            .collect();
+        let count = self.seen.entry(base.clone()).or_insert(0);
+        let slug = if *count == 0 {
+            base
+        } else {
+            format!("{}-{}", base, count)
+        };
+        *count += 1;
+        slug
+    }
+}
+
+/// The Markdown table of contents: one link per included file, pointing at
+/// the anchor of its `##` heading.
+fn markdown_toc(files: &[DocumentFile]) -> String {
+    let mut slugger = Slugger::default();
+    for heading in ["Directory structure", "Contents", "Files"] {
+        slugger.slug(heading);
+    }
+    let mut output = String::from("# Contents\n\n");
+    for file in files.iter().filter(|file| file.included) {
+        let _ = writeln!(
+            output,
+            "- [`{}`](#{})",
+            file.path.replace('`', ""),
+            slugger.slug(&file.path)
+        );
+    }
+    output.push('\n');
+    output
+}
+
+/// Renders the tree as a top-level section followed by one code block per
+/// included file, or as a single JSON object. `toc` adds a linked table of
+/// contents to Markdown documents.
+pub fn render(
+    format: MarkupFormat,
+    tree: &str,
+    files: &[DocumentFile],
+    toc: bool,
+) -> Result<String, GitRootError> {
+    let mut output = String::new();
+    let included = || files.iter().filter(|file| file.included);
+    match format {
+        MarkupFormat::Org => {
+            let _ = write!(
+                output,
+                "* Directory structure\n#+begin_example\n{}{}#+end_example\n\n* Files\n",
+                escape_org_block(tree),
+                ensure_newline(tree)
+            );
+            for file in included() {
+                let _ = writeln!(output, "** {}", file.path);
+                match file.block() {
+                    Some((language, content)) => {
+                        let _ = write!(
+                            output,
+                            "#+begin_src {}\n{}{}#+end_src\n",
+                            language.unwrap_or("text"),
+                            escape_org_block(content),
+                            ensure_newline(content)
+                        );
+                    }
+                    None => output.push_str(&file.placeholder()),
+                }
+            }
+        }
+        MarkupFormat::Typst => {
+            let fence = backtick_fence(tree);
+            let _ = write!(
+                output,
+                "= Directory structure\n{fence}\n{}{}{fence}\n\n= Files\n",
+                tree,
+                ensure_newline(tree)
+            );
+            for file in included() {
+                let _ = writeln!(output, "== `{}`", file.path);
+                match file.block() {
+                    Some((language, content)) => {
+                        let fence = backtick_fence(content);
+                        let _ = write!(
+                            output,
+                            "{fence}{}\n{}{}{fence}\n",
+                            language.unwrap_or(""),
+                            content,
+                            ensure_newline(content)
+                        );
+                    }
+                    None => output.push_str(&file.placeholder()),
+                }
+            }
+        }
+        MarkupFormat::Markdown => {
+            let fence = backtick_fence(tree);
+            let _ = write!(
+                output,
+                "# Directory structure\n\n{fence}tree\n{}{}{fence}\n\n{}# Files\n",
+                tree,
+                ensure_newline(tree),
+                if toc {
+                    markdown_toc(files)
+                } else {
+                    String::new()
+                }
+            );
+            for file in included() {
+                let _ = writeln!(output, "\n## {}\n", file.path);
+                match file.block() {
+                    Some((language, content)) => {
+                        let fence = backtick_fence(content);
+                        let _ = write!(
+                            output,
+                            "{fence}{}\n{}{}{fence}\n",
+                            language.unwrap_or(""),
+                            content,
+                            ensure_newline(content)
+                        );
+                    }
+                    None => output.push_str(&file.placeholder()),
+                }
+            }
+        }
+        MarkupFormat::Json => {
+            output = serde_json::to_string_pretty(&JsonDocument { tree, files })?;
+            output.push('\n');
+        }
+    }
+    Ok(output)
+}
@@ -0,0 +1,158 @@
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// Where directories go relative to the files of the same directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryOrder {
+    #[default]
+    DirsFirst,
+    FilesFirst,
+}
+
+impl EntryOrder {
+    pub fn new(files_first: bool) -> Self {
+        if files_first {
+            EntryOrder::FilesFirst
+        } else {
+            EntryOrder::DirsFirst
+        }
+    }
+
+    /// Orders two entries of one directory: by kind, then naturally by name.
+    pub fn compare(self, a: &str, a_is_dir: bool, b: &str, b_is_dir: bool) -> Ordering {
+        let kind = match self {
+            EntryOrder::DirsFirst => b_is_dir.cmp(&a_is_dir),
+            EntryOrder::FilesFirst => a_is_dir.cmp(&b_is_dir),
+        };
+        kind.then_with(|| compare_names(a, b))
+    }
+
+    /// Orders two paths as they appear in a pre-order listing of the tree.
+    /// `a_is_dir` and `b_is_dir` say whether the last component is a directory;
+    /// every earlier component is one.
+    pub fn compare_paths(self, a: &Path, a_is_dir: bool, b: &Path, b_is_dir: bool) -> Ordering {
+        let a_names: Vec<_> = a
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        let b_names: Vec<_> = b
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        for (i, (a_name, b_name)) in a_names.iter().zip(&b_names).enumerate() {
+            if a_name != b_name {
+                let a_dir = i + 1 < a_names.len() || a_is_dir;
+                let b_dir = i + 1 < b_names.len() || b_is_dir;
+                return self.compare(a_name, a_dir, b_name, b_dir);
+            }
+        }
+        a_names.len().cmp(&b_names.len())
+    }
+}
+
+/// Compares names with runs of digits taken by numeric value, so `item2`
+/// sorts before `item10`. Names that only differ in leading zeros fall back
+/// to plain string order.
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    let (mut a_rest, mut b_rest) = (a, b);
+    loop {
+        let (Some(a_char), Some(b_char)) = (a_rest.chars().next(), b_rest.chars().next()) else {
+            return a_rest.len().cmp(&b_rest.len()).then_with(|| a.cmp(b));
+        };
+        if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+            let a_digits = digit_run(a_rest);
+            let b_digits = digit_run(b_rest);
+            let a_value = a_digits.trim_start_matches('0');
+            let b_value = b_digits.trim_start_matches('0');
+            let by_value = a_value.len().cmp(&b_value.len()).then(a_value.cmp(b_value));
+            if by_value != Ordering::Equal {
+                return by_value;
+            }
+            a_rest = &a_rest[a_digits.len()..];
+            b_rest = &b_rest[b_digits.len()..];
+            continue;
+        }
+        if a_char != b_char {
+            return a_char.cmp(&b_char);
+        }
+        a_rest = &a_rest[a_char.len_utf8()..];
+        b_rest = &b_rest[b_char.len_utf8()..];
+    }
+}
+
+fn digit_run(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&'static str]) -> Vec<&'static str> {
+        let mut names = names.to_vec();
+        names.sort_by(|a, b| compare_names(a, b));
+        names
+    }
+
+    #[test]
+    fn digit_runs_sort_by_value() {
+        assert_eq!(
+            sorted(&["item10", "item2", "item1", "item20", "item3"]),
+            ["item1", "item2", "item3", "item10", "item20"]
+        );
+        assert_eq!(
+            sorted(&["v1.10.0", "v1.9.2", "v1.9.10"]),
+            ["v1.9.2", "v1.9.10", "v1.10.0"]
+        );
+    }
+
+    #[test]
+    fn leading_zeros_fall_back_to_string_order() {
+        assert_eq!(sorted(&["a01", "a1", "a001"]), ["a001", "a01", "a1"]);
+        assert_eq!(compare_names("a1", "a1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn directories_go_first_unless_files_first() {
+        let entries = [("b.txt", false), ("a", true), ("c", true), ("a.txt", false)];
+        let order_of = |order: EntryOrder| {
+            let mut entries = entries.to_vec();
+            entries.sort_by(|(a, a_dir), (b, b_dir)| order.compare(a, *a_dir, b, *b_dir));
+            entries
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            order_of(EntryOrder::DirsFirst),
+            ["a", "c", "a.txt", "b.txt"]
+        );
+        assert_eq!(
+            order_of(EntryOrder::FilesFirst),
+            ["a.txt", "b.txt", "a", "c"]
+        );
+    }
+
+    #[test]
+    fn paths_sort_in_tree_pre_order() {
+        let mut paths = vec![
+            Path::new("z.txt"),
+            Path::new("src/item10.rs"),
+            Path::new("src/nested/mod.rs"),
+            Path::new("src/item2.rs"),
+        ];
+        paths.sort_by(|a, b| EntryOrder::DirsFirst.compare_paths(a, false, b, false));
+        assert_eq!(
+            paths,
+            [
+                Path::new("src/nested/mod.rs"),
+                Path::new("src/item2.rs"),
+                Path::new("src/item10.rs"),
+                Path::new("z.txt"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+const FALLBACK_LIMIT: usize = 256;
+const EMFILE_RETRIES: u32 = 8;
+const EMFILE_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Counting semaphore bounding how many files are open for reading at once.
+#[derive(Debug)]
+struct OpenFileLimiter {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+static LIMITER: OnceLock<OpenFileLimiter> = OnceLock::new();
+
+/// Sets the maximum number of concurrently open files. Only the first call has an effect.
+pub fn set_max_open_files(limit: usize) {
+    let _ = LIMITER.set(OpenFileLimiter {
+        available: Mutex::new(limit.max(1)),
+        released: Condvar::new(),
+    });
+}
+
+/// Half of the soft `RLIMIT_NOFILE`, leaving headroom for stdio, git and the output file.
+pub fn default_max_open_files() -> usize {
+    soft_nofile_limit()
+        .map(|limit| (limit / 2).max(1))
+        .unwrap_or(FALLBACK_LIMIT)
+}
+
+#[cfg(target_os = "linux")]
+fn soft_nofile_limit() -> Option<usize> {
+    let limits = fs::read_to_string("/proc/self/limits").ok()?;
+    let line = limits.lines().find(|l| l.starts_with("Max open files"))?;
+    line["Max open files".len()..]
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn soft_nofile_limit() -> Option<usize> {
+    None
+}
+
+fn limiter() -> &'static OpenFileLimiter {
+    LIMITER.get_or_init(|| OpenFileLimiter {
+        available: Mutex::new(default_max_open_files()),
+        released: Condvar::new(),
+    })
+}
+
+fn is_emfile(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        // EMFILE is 24 on every Unix we support.
+        err.raw_os_error() == Some(24)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = err;
+        false
+    }
+}
+
+/// Reads a whole file while holding an open-file permit, backing off and
+/// retrying if the process still runs out of descriptors.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let limiter = limiter();
+    {
+        let mut available = limiter.available.lock().unwrap_or_else(|e| e.into_inner());
+        while *available == 0 {
+            available = limiter
+                .released
+                .wait(available)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        *available -= 1;
+    }
+
+    let mut attempt = 0;
+    let result = loop {
+        match fs::read(path) {
+            Err(err) if is_emfile(&err) && attempt < EMFILE_RETRIES => {
+                thread::sleep(EMFILE_BACKOFF * 2u32.pow(attempt));
+                attempt += 1;
+            }
+            result => break result,
+        }
+    };
+
+    *limiter.available.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+    limiter.released.notify_one();
+    result
+}
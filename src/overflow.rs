@@ -0,0 +1,55 @@
+use crate::git_ref::FileSource;
+use crate::tokens;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_MAX_FILES: usize = 500;
+pub const DEFAULT_MAX_TOKENS: usize = 200_000;
+
+/// How many of the largest files are listed alongside the warning.
+const TOP_CONTRIBUTORS: usize = 5;
+
+/// Thresholds above which the output is likely to overflow a model's context window.
+#[derive(Debug, Clone, Copy)]
+pub struct OverflowGuard {
+    pub max_files: usize,
+    pub max_tokens: usize,
+}
+
+impl OverflowGuard {
+    /// Returns a warning naming the largest files when `output` crosses either threshold.
+    pub fn check(
+        &self,
+        output: &str,
+        content_files: &[PathBuf],
+        root: &Path,
+        source: FileSource,
+    ) -> Option<String> {
+        let total_tokens = tokens::estimate(output);
+        if content_files.len() <= self.max_files && total_tokens <= self.max_tokens {
+            return None;
+        }
+
+        let mut sizes: Vec<(String, u64)> = content_files
+            .iter()
+            .filter_map(|abs_path| {
+                let size = source.size(abs_path)?;
+                let rel_path = abs_path.strip_prefix(root).unwrap_or(abs_path);
+                Some((rel_path.to_string_lossy().replace('\\', "/"), size))
+            })
+            .collect();
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut warning = format!(
+            "WARNING: output is large ({} files, ~{} tokens; limits {} files, {} tokens).\n\
+             Consider narrowing it with --include/--exclude. Largest files:\n",
+            content_files.len(),
+            total_tokens,
+            self.max_files,
+            self.max_tokens
+        );
+        for (path, size) in sizes.iter().take(TOP_CONTRIBUTORS) {
+            warning.push_str(&format!("  ~{:<8} {}\n", size.div_ceil(4), path));
+        }
+        Some(warning)
+    }
+}
@@ -0,0 +1,59 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+/// The `--progress` indicator on stderr: a spinner counting files while the
+/// repository is walked, then a bar while contents are read. Without the
+/// flag, or when stderr is not a terminal, every method does nothing.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    pub fn new(enabled: bool) -> Self {
+        let bar = (enabled && io::stderr().is_terminal())
+            .then(|| ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr()));
+        Progress { bar }
+    }
+
+    /// Switches to a spinner counting the files found so far.
+    pub fn start_scan(&self) {
+        if let Some(bar) = &self.bar {
+            bar.set_style(
+                ProgressStyle::with_template("{spinner} Scanning: {pos} file(s) found")
+                    .expect("valid progress template"),
+            );
+            bar.unset_length();
+            bar.reset();
+            bar.enable_steady_tick(Duration::from_millis(100));
+        }
+    }
+
+    /// Switches to a bar over `total` files to read.
+    pub fn start_read(&self, total: usize) {
+        if let Some(bar) = &self.bar {
+            bar.set_style(
+                ProgressStyle::with_template("Reading [{bar:30}] {pos}/{len} file(s)")
+                    .expect("valid progress template")
+                    .progress_chars("=> "),
+            );
+            bar.set_length(total as u64);
+            bar.reset();
+        }
+    }
+
+    /// Counts one more file found or read.
+    pub fn inc(&self) {
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+
+    /// Removes the indicator from the terminal.
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
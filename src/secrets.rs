@@ -0,0 +1,74 @@
+/// A built-in `--redact` pattern. Only the `secret` capture group is
+/// replaced, so keys, quotes and delimiters around the value survive.
+#[derive(Debug)]
+pub struct SecretPattern {
+    pub label: &'static str,
+    pub regex: &'static str,
+    /// Only redact values that look machine-generated, so that assignments
+    /// such as `password = read_password()` are left alone.
+    pub random_only: bool,
+}
+
+/// Every pattern `--redact` applies, in order.
+pub const PATTERNS: [SecretPattern; 7] = [
+    SecretPattern {
+        label: "private key block",
+        regex: r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----\r?\n(?P<secret>.*?)\r?\n-----END [A-Z ]*PRIVATE KEY-----",
+        random_only: false,
+    },
+    SecretPattern {
+        label: "AWS access key ID",
+        regex: r"\b(?P<secret>(?:AKIA|ASIA)[0-9A-Z]{16})\b",
+        random_only: false,
+    },
+    SecretPattern {
+        label: "AWS secret access key",
+        regex: r#"(?i)aws_secret_access_key["']?\s*[:=]\s*["']?(?P<secret>[A-Za-z0-9/+=]{40})"#,
+        random_only: false,
+    },
+    SecretPattern {
+        label: "GitHub token",
+        regex: r"\b(?P<secret>(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,}))\b",
+        random_only: false,
+    },
+    SecretPattern {
+        label: "Slack token",
+        regex: r"\b(?P<secret>xox[abposr]-[A-Za-z0-9-]{10,})",
+        random_only: false,
+    },
+    SecretPattern {
+        label: "bearer token",
+        regex: r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9\-._~+/]{16,}=*)",
+        random_only: false,
+    },
+    SecretPattern {
+        label: "secret assignment",
+        regex: r#"(?i)\b[a-z0-9_.-]*(?:secret|token|passw(?:or)?d|api_?key|access_?key|private_?key|credentials?)[a-z0-9_.-]*["']?\s*[:=]\s*["']?(?P<secret>[A-Za-z0-9+/=_\-.~]{12,})"#,
+        random_only: true,
+    },
+];
+
+/// Whether `value` mixes letters and digits with enough Shannon entropy
+/// (bits per character) to be a generated key rather than a word or name.
+pub fn looks_random(value: &str) -> bool {
+    const MIN_ENTROPY: f64 = 3.0;
+    let has_digit = value.chars().any(|c| c.is_ascii_digit());
+    let has_letter = value.chars().any(|c| c.is_ascii_alphabetic());
+    if !has_digit || !has_letter {
+        return false;
+    }
+    let mut counts = [0usize; 256];
+    for byte in value.bytes() {
+        counts[usize::from(byte)] += 1;
+    }
+    let len = value.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy >= MIN_ENTROPY
+}
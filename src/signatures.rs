@@ -0,0 +1,530 @@
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+const ELIDED_BLOCK: &str = "{ ... }";
+const ELIDED_SUITE: &str = "...";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    Python,
+    TypeScript,
+}
+
+impl Language {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+            _ => None,
+        }
+    }
+}
+
+/// Reduces a source file to its declarations, replacing bodies with `{ ... }`
+/// (or `...` for Python).
+///
+/// Returns `None` when the language is not supported, in which case callers
+/// should emit the file unchanged.
+pub fn extract_signatures(path: &Path, content: &str) -> Option<String> {
+    let language = Language::from_path(path)?;
+
+    #[cfg(feature = "tree-sitter")]
+    if let Some(output) = syntax::extract(language, path, content) {
+        return Some(output);
+    }
+
+    Some(match language {
+        Language::Rust | Language::TypeScript => extract_braced(language, content),
+        Language::Python => extract_indented(content),
+    })
+}
+
+static RUST_DECLARATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(pub(\([^)]*\))?\s+)?((const|async|unsafe|extern(\s+\x22[^\x22]*\x22)?|default)\s+)*(fn|struct|enum|union|trait|impl|type|const|static|mod|macro_rules!)\b",
+    )
+    .unwrap()
+});
+
+static RUST_CONTAINER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(pub(\([^)]*\))?\s+)?((unsafe|default)\s+)*(impl|trait|mod)\b").unwrap()
+});
+
+static TS_DECLARATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(export\s+)?(default\s+)?(declare\s+)?(abstract\s+)?(async\s+)?(function\*?|class|interface|type|enum|namespace|module|const|let)\b",
+    )
+    .unwrap()
+});
+
+static TS_CONTAINER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(export\s+)?(default\s+)?(declare\s+)?(abstract\s+)?(class|interface|namespace|module)\b",
+    )
+    .unwrap()
+});
+
+/// Counts `{` and `}` on a line, ignoring string literals and `//` comments.
+fn count_braces(line: &str) -> (usize, usize) {
+    let (mut opens, mut closes) = (0, 0);
+    let mut chars = line.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                if c == '\\' {
+                    chars.next();
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '`' => quote = Some(c),
+                '/' if chars.peek() == Some(&'/') => break,
+                '{' => opens += 1,
+                '}' => closes += 1,
+                _ => {}
+            },
+        }
+    }
+    (opens, closes)
+}
+
+/// Line-based fallback for brace-delimited languages.
+///
+/// Declarations at the top level, and members directly inside `impl`, `trait`,
+/// `class` and similar containers, are kept; every other block is elided.
+fn extract_braced(language: Language, content: &str) -> String {
+    let (declaration, container) = match language {
+        Language::Rust => (&*RUST_DECLARATION, &*RUST_CONTAINER),
+        _ => (&*TS_DECLARATION, &*TS_CONTAINER),
+    };
+
+    let mut output = String::new();
+    let mut depth = 0usize;
+    // Depths at which container bodies (impl/class/...) are being walked.
+    let mut containers: Vec<usize> = Vec::new();
+    // While eliding a body, the depth at which it ends.
+    let mut skip_to: Option<usize> = None;
+    // A declaration whose signature spans several lines.
+    let mut pending = String::new();
+
+    for line in content.lines() {
+        let (opens, closes) = count_braces(line);
+        let trimmed = line.trim();
+
+        if let Some(target) = skip_to {
+            depth = (depth + opens).saturating_sub(closes);
+            if depth <= target {
+                skip_to = None;
+            }
+            continue;
+        }
+
+        let in_container = containers.last() == Some(&depth);
+        let visible = depth == 0 || in_container;
+
+        if visible && pending.is_empty() {
+            if trimmed.starts_with('}') && in_container {
+                containers.pop();
+                output.push_str(line);
+                output.push('\n');
+                depth = (depth + opens).saturating_sub(closes);
+                continue;
+            }
+
+            let is_member = in_container
+                && !trimmed.is_empty()
+                && !trimmed.starts_with("//")
+                && !trimmed.starts_with("/*")
+                && !trimmed.starts_with('*');
+            let is_declaration = declaration.is_match(trimmed) || is_member;
+
+            if is_declaration && container.is_match(trimmed) && opens > closes {
+                output.push_str(line);
+                output.push('\n');
+                depth += opens - closes;
+                containers.push(depth);
+                continue;
+            }
+
+            if is_declaration {
+                pending.push_str(line);
+            }
+        } else if !pending.is_empty() {
+            pending.push('\n');
+            pending.push_str(line);
+        }
+
+        if !pending.is_empty() {
+            if opens > closes {
+                // The body starts on this line: keep everything before the brace.
+                let signature = match pending.rfind('{') {
+                    Some(idx) => pending[..idx].trim_end(),
+                    None => pending.trim_end(),
+                };
+                output.push_str(signature);
+                output.push(' ');
+                output.push_str(ELIDED_BLOCK);
+                output.push('\n');
+                skip_to = Some(depth);
+                pending.clear();
+            } else if opens > 0 && opens == closes && trimmed.ends_with('}') {
+                // A one-line body such as `fn f() -> u32 { 1 }`.
+                let line_start = pending.rfind('\n').map_or(0, |idx| idx + 1);
+                let brace = pending[line_start..]
+                    .find('{')
+                    .map_or(pending.len(), |idx| line_start + idx);
+                output.push_str(pending[..brace].trim_end());
+                output.push(' ');
+                output.push_str(ELIDED_BLOCK);
+                output.push('\n');
+                pending.clear();
+            } else if trimmed.ends_with(';')
+                || trimmed.ends_with('}')
+                || (language == Language::TypeScript && !trimmed.ends_with(['(', ',', '=']))
+            {
+                output.push_str(&pending);
+                output.push('\n');
+                pending.clear();
+            }
+        } else if opens > closes {
+            skip_to = Some(depth);
+        }
+
+        depth = (depth + opens).saturating_sub(closes);
+    }
+
+    if !pending.is_empty() {
+        output.push_str(&pending);
+        output.push('\n');
+    }
+    output
+}
+
+static PY_DECLARATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(async\s+def|def|class)\b").unwrap());
+
+/// Indentation-based fallback for Python: keeps `def`/`class` headers and
+/// their decorators, replacing function bodies with `...`.
+fn extract_indented(content: &str) -> String {
+    let mut output = String::new();
+    // Indentation of the function whose body is being skipped.
+    let mut skip_indent: Option<usize> = None;
+    // Indentation levels of classes whose bodies are being walked.
+    let mut classes: Vec<usize> = Vec::new();
+    let mut decorators = String::new();
+    let mut header = String::new();
+    let mut header_indent = 0;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if !header.is_empty() {
+            header.push('\n');
+            header.push_str(line);
+            if trimmed.trim_end().ends_with(':') {
+                output.push_str(&header);
+                output.push(' ');
+                output.push_str(ELIDED_SUITE);
+                output.push('\n');
+                header.clear();
+                skip_indent = Some(header_indent);
+            }
+            continue;
+        }
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(level) = skip_indent {
+            if indent > level {
+                continue;
+            }
+            skip_indent = None;
+        }
+        while classes.last().is_some_and(|&level| indent <= level) {
+            classes.pop();
+        }
+
+        let visible = indent == 0 || classes.last().is_some_and(|&level| indent > level);
+        if !visible {
+            continue;
+        }
+
+        if trimmed.starts_with('@') {
+            decorators.push_str(line);
+            decorators.push('\n');
+            continue;
+        }
+
+        if !PY_DECLARATION.is_match(trimmed) {
+            decorators.clear();
+            continue;
+        }
+
+        output.push_str(&decorators);
+        decorators.clear();
+
+        if trimmed.starts_with("class") {
+            output.push_str(line);
+            output.push('\n');
+            classes.push(indent);
+        } else if trimmed.trim_end().ends_with(':') {
+            output.push_str(line);
+            output.push(' ');
+            output.push_str(ELIDED_SUITE);
+            output.push('\n');
+            skip_indent = Some(indent);
+        } else {
+            header.push_str(line);
+            header_indent = indent;
+        }
+    }
+
+    if !header.is_empty() {
+        output.push_str(&header);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(feature = "tree-sitter")]
+mod syntax {
+    use super::{ELIDED_BLOCK, ELIDED_SUITE, Language};
+    use std::ops::Range;
+    use std::path::Path;
+    use tree_sitter::{Node, Parser};
+
+    pub(super) fn extract(language: Language, path: &Path, content: &str) -> Option<String> {
+        let grammar: tree_sitter::Language = match language {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::TypeScript => match path.extension().and_then(|e| e.to_str()) {
+                Some("tsx") => tree_sitter_typescript::LANGUAGE_TSX.into(),
+                _ => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            },
+        };
+        let mut parser = Parser::new();
+        parser.set_language(&grammar).ok()?;
+        let tree = parser.parse(content, None)?;
+        let root = tree.root_node();
+        if root.has_error() {
+            // Let the line-based fallback deal with code tree-sitter can't parse.
+            return None;
+        }
+
+        let mut output = String::new();
+        let mut cursor = root.walk();
+        for node in root.named_children(&mut cursor) {
+            if !is_declaration(language, node.kind()) {
+                continue;
+            }
+            let mut elisions = Vec::new();
+            collect_elisions(language, node, &mut elisions);
+            output.push_str(&render(content, node.byte_range(), &elisions, language));
+            output.push('\n');
+        }
+        Some(output)
+    }
+
+    fn is_declaration(language: Language, kind: &str) -> bool {
+        match language {
+            Language::Rust => matches!(
+                kind,
+                "function_item"
+                    | "function_signature_item"
+                    | "struct_item"
+                    | "enum_item"
+                    | "union_item"
+                    | "trait_item"
+                    | "impl_item"
+                    | "type_item"
+                    | "const_item"
+                    | "static_item"
+                    | "mod_item"
+                    | "macro_definition"
+            ),
+            Language::Python => matches!(
+                kind,
+                "function_definition" | "class_definition" | "decorated_definition"
+            ),
+            Language::TypeScript => matches!(
+                kind,
+                "function_declaration"
+                    | "generator_function_declaration"
+                    | "class_declaration"
+                    | "abstract_class_declaration"
+                    | "interface_declaration"
+                    | "type_alias_declaration"
+                    | "enum_declaration"
+                    | "internal_module"
+                    | "module"
+                    | "export_statement"
+                    | "lexical_declaration"
+                    | "ambient_declaration"
+            ),
+        }
+    }
+
+    /// Records the byte ranges of bodies that should be replaced.
+    fn collect_elisions(language: Language, node: Node, out: &mut Vec<Range<usize>>) {
+        let elide_body = match language {
+            Language::Rust => matches!(
+                node.kind(),
+                "function_item" | "struct_item" | "enum_item" | "union_item"
+            ),
+            Language::Python => node.kind() == "function_definition",
+            Language::TypeScript => matches!(
+                node.kind(),
+                "function_declaration"
+                    | "generator_function_declaration"
+                    | "method_definition"
+                    | "function_expression"
+                    | "arrow_function"
+            ),
+        };
+        if elide_body && let Some(body) = node.child_by_field_name("body") {
+            out.push(body.byte_range());
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            collect_elisions(language, child, out);
+        }
+    }
+
+    fn render(
+        content: &str,
+        range: Range<usize>,
+        elisions: &[Range<usize>],
+        language: Language,
+    ) -> String {
+        let placeholder = match language {
+            Language::Python => ELIDED_SUITE,
+            _ => ELIDED_BLOCK,
+        };
+        let mut output = String::new();
+        let mut position = range.start;
+        for elided in elisions {
+            output.push_str(&content[position..elided.start]);
+            output.push_str(placeholder);
+            position = elided.end;
+        }
+        output.push_str(&content[position..range.end]);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST: &str = "\
+use std::fmt;
+
+pub struct Point {
+    x: i32,
+}
+
+impl Point {
+    pub fn new(x: i32) -> Self {
+        let doubled = x * 2;
+        Point { x: doubled }
+    }
+}
+
+fn helper() -> i32 {
+    42
+}
+";
+
+    const PYTHON: &str = "\
+import os
+
+class Greeter:
+    def greet(self, name):
+        message = 'hi ' + name
+        return message
+
+def main():
+    print(os.getcwd())
+";
+
+    const TYPESCRIPT: &str = "\
+export interface Shape {
+  area(): number;
+}
+
+export class Square {
+  constructor(private side: number) {}
+  area(): number {
+    const result = this.side * this.side;
+    return result;
+  }
+}
+
+export function describe(shape: Shape): string {
+  return `area ${shape.area()}`;
+}
+";
+
+    fn extract(path: &str, content: &str) -> String {
+        extract_signatures(Path::new(path), content).expect("supported language")
+    }
+
+    #[test]
+    fn rust_keeps_signatures_and_elides_bodies() {
+        for output in [
+            extract("lib.rs", RUST),
+            extract_braced(Language::Rust, RUST),
+        ] {
+            assert!(output.contains("pub struct Point"), "{}", output);
+            assert!(output.contains("pub fn new(x: i32) -> Self"), "{}", output);
+            assert!(output.contains("fn helper() -> i32"), "{}", output);
+            assert!(output.contains(ELIDED_BLOCK), "{}", output);
+            assert!(!output.contains("let doubled"), "{}", output);
+            assert!(!output.contains("42"), "{}", output);
+        }
+    }
+
+    #[test]
+    fn python_keeps_signatures_and_elides_bodies() {
+        for output in [extract("app.py", PYTHON), extract_indented(PYTHON)] {
+            assert!(output.contains("class Greeter:"), "{}", output);
+            assert!(output.contains("def greet(self, name):"), "{}", output);
+            assert!(output.contains("def main():"), "{}", output);
+            assert!(output.contains(ELIDED_SUITE), "{}", output);
+            assert!(!output.contains("message ="), "{}", output);
+            assert!(!output.contains("getcwd"), "{}", output);
+        }
+    }
+
+    #[test]
+    fn typescript_keeps_signatures_and_elides_bodies() {
+        for output in [
+            extract("shape.ts", TYPESCRIPT),
+            extract_braced(Language::TypeScript, TYPESCRIPT),
+        ] {
+            assert!(output.contains("export class Square"), "{}", output);
+            assert!(output.contains("area(): number"), "{}", output);
+            assert!(
+                output.contains("export function describe(shape: Shape): string"),
+                "{}",
+                output
+            );
+            assert!(!output.contains("const result"), "{}", output);
+            assert!(!output.contains("return `area"), "{}", output);
+        }
+    }
+
+    #[test]
+    fn unsupported_languages_are_left_alone() {
+        assert_eq!(extract_signatures(Path::new("notes.md"), "# Notes\n"), None);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::formatter::QuoteStyle;
+use crate::git_ref::FileSource;
+use crate::tokens;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// How many of the largest content files `--stats` lists.
+const LARGEST_FILES: usize = 5;
+
+/// What a run would emit, gathered from file sizes and the raw contents
+/// before any transform.
+#[derive(Debug)]
+pub struct ScanStats {
+    content_files: usize,
+    tree_only_files: usize,
+    total_bytes: u64,
+    /// [`tokens::estimate`] summed over the raw contents of every file.
+    total_tokens: usize,
+    /// Extension (or `(none)`) -> file count and bytes, largest first.
+    extensions: Vec<(String, usize, u64)>,
+    largest: Vec<(String, u64)>,
+}
+
+impl ScanStats {
+    /// `relative_files` is every listed file; `content_files` the absolute
+    /// paths of those whose contents would be emitted.
+    pub fn collect(
+        relative_files: &[PathBuf],
+        content_files: &[PathBuf],
+        root: &Path,
+        source: FileSource,
+    ) -> Self {
+        let content_set: HashSet<&Path> = content_files
+            .iter()
+            .filter_map(|abs_path| abs_path.strip_prefix(root).ok())
+            .collect();
+        let mut sizes: Vec<(String, u64)> = content_files
+            .iter()
+            .map(|abs_path| {
+                let size = source.size(abs_path).unwrap_or(0);
+                let relative_path = abs_path.strip_prefix(root).unwrap_or(abs_path);
+                (relative_path.to_string_lossy().replace('\\', "/"), size)
+            })
+            .collect();
+
+        let mut by_extension: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+        for (path, size) in &sizes {
+            let extension = Path::new(path).extension().map_or_else(
+                || "(none)".to_string(),
+                |ext| ext.to_string_lossy().into_owned(),
+            );
+            let entry = by_extension.entry(extension).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        }
+        let mut extensions: Vec<(String, usize, u64)> = by_extension
+            .into_iter()
+            .map(|(extension, (count, bytes))| (extension, count, bytes))
+            .collect();
+        extensions.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let total_bytes = sizes.iter().map(|(_, size)| size).sum();
+        let total_tokens = content_files
+            .iter()
+            .filter_map(|abs_path| source.read(abs_path).ok())
+            .map(|bytes| tokens::estimate(&String::from_utf8_lossy(&bytes)))
+            .sum();
+        sizes.truncate(LARGEST_FILES);
+        ScanStats {
+            content_files: content_files.len(),
+            tree_only_files: relative_files
+                .iter()
+                .filter(|path| !content_set.contains(path.as_path()))
+                .count(),
+            total_bytes,
+            total_tokens,
+            extensions,
+            largest: sizes,
+        }
+    }
+
+    /// The `--summary` footer: a single `<summary>` element with the totals.
+    pub fn render_summary(&self, quote_style: QuoteStyle) -> String {
+        format!(
+            "<summary{}{}{}{}/>\n",
+            quote_style.attr("files", &self.content_files.to_string()),
+            quote_style.attr("tree-only", &self.tree_only_files.to_string()),
+            quote_style.attr("bytes", &self.total_bytes.to_string()),
+            quote_style.attr("tokens", &self.total_tokens.to_string())
+        )
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "Files with contents: {}", self.content_files);
+        let _ = writeln!(output, "Tree-only files:     {}", self.tree_only_files);
+        let _ = writeln!(
+            output,
+            "Content bytes:       {} (~{} tokens)",
+            self.total_bytes, self.total_tokens
+        );
+        if !self.extensions.is_empty() {
+            let width = self.extensions.iter().map(|e| e.0.len()).max().unwrap_or(0);
+            let _ = writeln!(output, "\nBy extension:");
+            for (extension, count, bytes) in &self.extensions {
+                let _ = writeln!(
+                    output,
+                    "  {:<width$}  {:>5} file(s)  {:>10} bytes",
+                    extension,
+                    count,
+                    bytes,
+                    width = width
+                );
+            }
+        }
+        if !self.largest.is_empty() {
+            let _ = writeln!(output, "\nLargest files:");
+            for (path, size) in &self.largest {
+                let _ = writeln!(output, "  {:>10} bytes  {}", size, path);
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,214 @@
+use crate::GitRootError;
+use crate::formatter;
+use crate::markup::DocumentFile;
+
+/// Placeholders available to `--template`, filled in per file.
+const FILE_FIELDS: [&str; 4] = ["path", "content", "lang", "lines"];
+/// Placeholders available to `--document-template`.
+const DOCUMENT_FIELDS: [&str; 2] = ["tree", "files"];
+
+const DEFAULT_FILE: &str = "<file src=\"{path}\">\n{content}</file>\n";
+const DEFAULT_DOCUMENT: &str = "<directory-structure>\n{tree}</directory-structure>\n\n{files}";
+
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    /// A placeholder, and whether it sits inside a quoted attribute value
+    /// (`name="{path}"`) and so must be escaped.
+    Field(&'static str, bool),
+}
+
+/// A template string split into literal text and `{name}` placeholders.
+#[derive(Debug)]
+struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Parses `source`, where `{{` and `}}` are literal braces and `\n`, `\t`
+    /// and `\\` are escapes. Any placeholder outside `fields` is an error.
+    fn parse(source: &str, fields: &[&'static str]) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(format!("unclosed placeholder {{{}", name));
+                    }
+                    let Some(field) = fields.iter().find(|field| **field == name) else {
+                        let expected: Vec<String> = fields
+                            .iter()
+                            .map(|field| format!("{{{}}}", field))
+                            .collect();
+                        return Err(format!(
+                            "unknown placeholder {{{}}}, expected one of {}",
+                            name,
+                            expected.join(", ")
+                        ));
+                    };
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                    parts.push(Part::Field(field, false));
+                }
+                '}' => return Err("unmatched '}' (write '}}' for a literal brace)".to_string()),
+                '\\' => match chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+        parts.push(Part::Text(text));
+        mark_attribute_fields(&mut parts);
+        Ok(Template { parts })
+    }
+
+    fn render(&self, value: impl Fn(&str) -> String) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(field, false) => value(field),
+                Part::Field(field, true) => formatter::escape_attr(&value(field)),
+            })
+            .collect()
+    }
+}
+
+/// Flags the fields that appear between the quotes of an attribute value,
+/// which opens with `="` or `='` and closes at the matching quote.
+fn mark_attribute_fields(parts: &mut [Part]) {
+    let mut open_quote = None;
+    let mut previous = None;
+    for part in parts {
+        match part {
+            Part::Text(text) => {
+                for c in text.chars() {
+                    open_quote = match open_quote {
+                        None if previous == Some('=') && matches!(c, '"' | '\'') => Some(c),
+                        Some(quote) if c == quote => None,
+                        open_quote => open_quote,
+                    };
+                    previous = Some(c);
+                }
+            }
+            Part::Field(_, in_attribute) => {
+                *in_attribute = open_quote.is_some();
+                previous = None;
+            }
+        }
+    }
+}
+
+/// `--template` and `--document-template`, either of which falls back to
+/// the XML layout when not given.
+#[derive(Debug)]
+pub struct OutputTemplate {
+    file: Template,
+    document: Template,
+}
+
+impl OutputTemplate {
+    /// Parses the templates, or returns `None` when neither is set.
+    pub fn parse(file: Option<&str>, document: Option<&str>) -> Result<Option<Self>, GitRootError> {
+        if file.is_none() && document.is_none() {
+            return Ok(None);
+        }
+        let file = Template::parse(file.unwrap_or(DEFAULT_FILE), &FILE_FIELDS)
+            .map_err(|e| GitRootError::InvalidTemplate("--template", e))?;
+        let document = Template::parse(document.unwrap_or(DEFAULT_DOCUMENT), &DOCUMENT_FIELDS)
+            .map_err(|e| GitRootError::InvalidTemplate("--document-template", e))?;
+        Ok(Some(OutputTemplate { file, document }))
+    }
+
+    /// Renders the document, with the file template applied to each included file.
+    pub fn render(&self, tree: &str, files: &[DocumentFile]) -> String {
+        let files: String = files
+            .iter()
+            .filter(|file| file.included)
+            .map(|file| {
+                self.file.render(|field| match field {
+                    "path" => file.path.clone(),
+                    "lang" => file.language.unwrap_or("").to_string(),
+                    "lines" => file
+                        .content
+                        .as_deref()
+                        .map_or(0, |content| content.lines().count())
+                        .to_string(),
+                    _ => file
+                        .block()
+                        .map_or_else(|| file.placeholder(), |(_, text)| text.to_string()),
+                })
+            })
+            .collect();
+        self.document.render(|field| match field {
+            "tree" => tree.to_string(),
+            _ => files.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> DocumentFile {
+        DocumentFile {
+            path: path.to_string(),
+            language: None,
+            included: true,
+            content: Some("body\n".to_string()),
+            binary: false,
+            content_base64: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn paths_in_attributes_are_escaped() {
+        let template = OutputTemplate::parse(None, Some("{files}"))
+            .expect("valid templates")
+            .expect("templates set");
+        assert_eq!(
+            template.render("", &[file("say \"hi\" & <bye>.txt")]),
+            "<file src=\"say &quot;hi&quot; &amp; &lt;bye&gt;.txt\">\nbody\n</file>\n"
+        );
+    }
+
+    #[test]
+    fn fields_outside_attributes_are_left_as_is() {
+        let template = OutputTemplate::parse(
+            Some("## {path} ({lang})\n<a title='{path}'>{content}</a>\n"),
+            Some("{files}"),
+        )
+        .expect("valid templates")
+        .expect("templates set");
+        assert_eq!(
+            template.render("", &[file("it's & co.md")]),
+            "## it's & co.md ()\n<a title='it&apos;s &amp; co.md'>body\n</a>\n"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+/// Rough token count for budgeting: about four characters per token.
+pub fn estimate(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A node of the `--tree-json` export.
+///
+/// Unlike the `--json` listing, every node carries its full relative path
+/// and whether its contents would be emitted, so a UI can render the tree
+/// without re-deriving either.
+#[derive(Debug, Serialize)]
+pub struct TreeJsonNode {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub include_content: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<TreeJsonNode>>,
+}
+
+#[derive(Default)]
+struct DirBuilder {
+    dirs: BTreeMap<String, DirBuilder>,
+    files: BTreeMap<String, bool>,
+}
+
+impl DirBuilder {
+    fn insert(&mut self, components: &[String], include_content: bool) {
+        match components {
+            [] => {}
+            [file] => {
+                self.files.insert(file.clone(), include_content);
+            }
+            [dir, rest @ ..] => self
+                .dirs
+                .entry(dir.clone())
+                .or_default()
+                .insert(rest, include_content),
+        }
+    }
+
+    /// Directories sort before files; a directory includes content when any descendant does.
+    fn into_nodes(self, parent: &str) -> Vec<TreeJsonNode> {
+        let join = |name: &str| {
+            if parent.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", parent, name)
+            }
+        };
+
+        let mut nodes = Vec::new();
+        for (name, dir) in self.dirs {
+            let path = join(&name);
+            let children = dir.into_nodes(&path);
+            nodes.push(TreeJsonNode {
+                include_content: children.iter().any(|c| c.include_content),
+                name,
+                path,
+                is_dir: true,
+                children: Some(children),
+            });
+        }
+        for (name, include_content) in self.files {
+            nodes.push(TreeJsonNode {
+                path: join(&name),
+                name,
+                is_dir: false,
+                include_content,
+                children: None,
+            });
+        }
+        nodes
+    }
+}
+
+/// Rebuilds the nested directory structure from a flat list of relative paths.
+pub fn build_tree_json(
+    relative_files: &[PathBuf],
+    content_files: &HashSet<&Path>,
+) -> Vec<TreeJsonNode> {
+    let mut root = DirBuilder::default();
+    for path in relative_files {
+        let components: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        root.insert(&components, content_files.contains(path.as_path()));
+    }
+    root.into_nodes("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn nests_a_small_fixture() {
+        let relative_files: Vec<PathBuf> = ["README.md", "src/main.rs", "src/util/io.rs"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let content_files: HashSet<&Path> = [Path::new("src/util/io.rs")].into_iter().collect();
+
+        let tree = build_tree_json(&relative_files, &content_files);
+        let expected = json!([
+            {
+                "name": "src",
+                "path": "src",
+                "is_dir": true,
+                "include_content": true,
+                "children": [
+                    {
+                        "name": "util",
+                        "path": "src/util",
+                        "is_dir": true,
+                        "include_content": true,
+                        "children": [
+                            {
+                                "name": "io.rs",
+                                "path": "src/util/io.rs",
+                                "is_dir": false,
+                                "include_content": true
+                            }
+                        ]
+                    },
+                    {
+                        "name": "main.rs",
+                        "path": "src/main.rs",
+                        "is_dir": false,
+                        "include_content": false
+                    }
+                ]
+            },
+            {
+                "name": "README.md",
+                "path": "README.md",
+                "is_dir": false,
+                "include_content": false
+            }
+        ]);
+        assert_eq!(serde_json::to_value(&tree).expect("serializes"), expected);
+    }
+}
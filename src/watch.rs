@@ -0,0 +1,63 @@
+use crate::GitRootError;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// How long the files must stay untouched after a change before the output
+/// is generated again, so a burst of changes (a save touching several files,
+/// a branch switch) leads to a single run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+enum Message {
+    Changed(notify::Result<Event>),
+    Interrupted,
+}
+
+/// Generates the output, then watches `root` and generates it again once a
+/// change to a path accepted by `is_watched` has settled. Ctrl-C lets the
+/// current run finish and returns.
+pub fn run(
+    root: &Path,
+    is_watched: impl Fn(&Path) -> bool,
+    mut generate: impl FnMut(),
+) -> Result<(), GitRootError> {
+    let (sender, receiver) = mpsc::channel();
+    let interrupt = sender.clone();
+    ctrlc::set_handler(move || {
+        let _ = interrupt.send(Message::Interrupted);
+    })
+    .map_err(GitRootError::WatchInterrupt)?;
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = sender.send(Message::Changed(event));
+    })
+    .map_err(GitRootError::Watch)?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(GitRootError::Watch)?;
+
+    generate();
+    eprintln!("Watching {} for changes, Ctrl-C to stop", root.display());
+    loop {
+        let event = match receiver.recv() {
+            Ok(Message::Changed(event)) => event.map_err(GitRootError::Watch)?,
+            Ok(Message::Interrupted) | Err(_) => return Ok(()),
+        };
+        if !changes_watched_path(&event, &is_watched) {
+            continue;
+        }
+        loop {
+            match receiver.recv_timeout(DEBOUNCE) {
+                Ok(Message::Changed(_)) => {}
+                Ok(Message::Interrupted) | Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+        generate();
+    }
+}
+
+/// Whether `event` modified a path accepted by `is_watched`; reads do not count.
+fn changes_watched_path(event: &Event, is_watched: impl Fn(&Path) -> bool) -> bool {
+    !matches!(event.kind, EventKind::Access(_)) && event.paths.iter().any(|path| is_watched(path))
+}
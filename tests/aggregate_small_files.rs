@@ -0,0 +1,30 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn small_files_share_one_block_while_a_large_file_keeps_its_own() {
+    let repo = TempRepo::new();
+    let large = format!("{}\n", "x".repeat(200));
+    repo.write("a.toml", "key = 1\n")
+        .write("b.txt", "two\n")
+        .write("large.txt", &large);
+
+    let output = repo.stdout(&["--aggregate-small-files", "20"]);
+    let (_, contents) = output
+        .split_once("</directory-structure>\n\n")
+        .expect("tree before the contents");
+    assert_eq!(
+        contents,
+        format!(
+            "<file src=\"large.txt\">\n{}</file>\n\
+             <small-files>\n\
+             --- a.toml ---\n\
+             key = 1\n\
+             --- b.txt ---\n\
+             two\n\
+             </small-files>\n",
+            large
+        )
+    );
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn an_empty_contents_block_is_emitted_when_nothing_is_selected() {
+    let repo = TempRepo::new();
+    repo.write("schema.sql", "create table t ();\n");
+
+    assert_eq!(
+        repo.stdout(&["--always-emit-contents", "--include-in-tree", "schema.sql"]),
+        "<directory-structure>\n\
+         [\n  {\n    \"name\": \"schema.sql\",\n    \"type\": \"file\"\n  }\n]\n\
+         </directory-structure>\n\
+         \n\
+         <file-contents>\n\
+         </file-contents>\n"
+    );
+}
+
+#[test]
+fn the_contents_block_is_omitted_without_the_flag() {
+    let repo = TempRepo::new();
+    repo.write("schema.sql", "create table t ();\n");
+
+    let output = repo.stdout(&["--include-in-tree", "schema.sql"]);
+    assert!(output.ends_with("</directory-structure>\n"), "{}", output);
+    assert!(!output.contains("file-contents"), "{}", output);
+}
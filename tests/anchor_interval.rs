@@ -0,0 +1,38 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn anchors_precede_every_nth_line() {
+    let repo = TempRepo::new();
+    let lines: String = (1..=7).map(|n| format!("line {}\n", n)).collect();
+    repo.write("n.txt", lines);
+
+    let output = repo.stdout(&["--anchor-interval", "3"]);
+    assert!(
+        output.contains(
+            "<file src=\"n.txt\">\n\
+             line 1\n\
+             line 2\n\
+             <!-- L3 -->\n\
+             line 3\n\
+             line 4\n\
+             line 5\n\
+             <!-- L6 -->\n\
+             line 6\n\
+             line 7\n\
+             </file>"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn files_shorter_than_the_interval_get_no_anchors() {
+    let repo = TempRepo::new();
+    repo.write("short.txt", "one\ntwo\n");
+
+    let output = repo.stdout(&["--anchor-interval", "3"]);
+    assert!(!output.contains("<!--"), "{}", output);
+}
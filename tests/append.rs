@@ -0,0 +1,49 @@
+mod common;
+
+use common::TempRepo;
+use std::fs;
+
+#[test]
+fn append_adds_only_the_changed_files() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "alpha one\n").write("b.txt", "beta\n");
+    let args = ["--output", "out.txt", "--append", "--exclude", "out.txt*"];
+
+    repo.stdout(&args);
+    let first = fs::read_to_string(repo.path("out.txt")).expect("read output");
+    assert!(
+        first.contains("alpha one") && first.contains("beta"),
+        "{}",
+        first
+    );
+    assert!(!first.contains("<update"), "{}", first);
+
+    repo.write("a.txt", "alpha two\n");
+    repo.stdout(&args);
+    let second = fs::read_to_string(repo.path("out.txt")).expect("read output");
+    assert!(second.starts_with(&first), "{}", second);
+    let update = &second[first.len()..];
+    assert!(update.contains("<update at=\""), "{}", update);
+    assert!(update.contains("alpha two"), "{}", update);
+    assert!(!update.contains("beta"), "{}", update);
+
+    // Nothing changed since, so nothing is appended.
+    repo.stdout(&args);
+    let third = fs::read_to_string(repo.path("out.txt")).expect("read output");
+    assert_eq!(third, second);
+}
+
+#[test]
+fn append_records_removed_files() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "alpha\n").write("b.txt", "beta\n");
+    let args = ["--output", "out.txt", "--append", "--exclude", "out.txt*"];
+
+    repo.stdout(&args);
+    let first = fs::read_to_string(repo.path("out.txt")).expect("read output");
+    repo.remove("b.txt");
+    repo.stdout(&args);
+    let second = fs::read_to_string(repo.path("out.txt")).expect("read output");
+    let update = &second[first.len()..];
+    assert!(update.contains("<removed src=\"b.txt\"/>"), "{}", update);
+}
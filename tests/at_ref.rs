@@ -0,0 +1,73 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn at_ref_emits_the_committed_contents_not_the_working_copy() {
+    let repo = TempRepo::new();
+    repo.write("notes.txt", "committed\n").commit_all("initial");
+    repo.write("notes.txt", "edited in the working tree\n");
+
+    let output = repo.stdout(&["--at-ref", "HEAD"]);
+    assert!(output.contains("committed\n"), "{}", output);
+    assert!(!output.contains("edited in the working tree"), "{}", output);
+}
+
+#[test]
+fn at_ref_takes_file_sizes_from_the_commit() {
+    let repo = TempRepo::new();
+    repo.write("small.txt", "tiny\n").commit_all("initial");
+    // Grown past the limit in the working tree only.
+    repo.write("small.txt", "x".repeat(4096));
+
+    let output = repo.stdout(&["--at-ref", "HEAD", "--max-file-size", "100"]);
+    assert!(output.contains("tiny\n"), "{}", output);
+}
+
+#[test]
+fn at_ref_does_not_read_files_missing_from_the_commit() {
+    let repo = TempRepo::new();
+    repo.write("kept.txt", "kept\n").commit_all("initial");
+    repo.write("untracked.txt", "only on disk\n");
+
+    let output = repo.stdout(&["--at-ref", "HEAD"]);
+    assert!(output.contains("kept\n"), "{}", output);
+    assert!(!output.contains("only on disk"), "{}", output);
+}
+
+#[test]
+fn at_ref_applies_force_include_and_force_exclude() {
+    let repo = TempRepo::new();
+    repo.write("keep.txt", "keep\n")
+        .write("forced.log", "forced\n")
+        .write("secret.txt", "secret\n")
+        .commit_all("initial");
+
+    let output = repo.stdout(&[
+        "--at-ref",
+        "HEAD",
+        "--exclude",
+        "*.log",
+        "--force-include",
+        "forced.log",
+        "--force-exclude",
+        "secret.txt",
+    ]);
+    assert!(output.contains("<file src=\"keep.txt\">"), "{}", output);
+    assert!(output.contains("<file src=\"forced.log\">"), "{}", output);
+    assert!(!output.contains("secret"), "{}", output);
+}
+
+#[test]
+fn at_ref_conflicts_with_tracked_only() {
+    let repo = TempRepo::new();
+    repo.write("notes.txt", "notes\n").commit_all("initial");
+
+    let output = repo.run(&["--at-ref", "HEAD", "--tracked-only"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("cannot be used with"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::TempRepo;
+
+fn content_files(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("<file src=\""))
+        .filter_map(|rest| rest.split('"').next())
+        .collect()
+}
+
+#[test]
+fn backslash_patterns_match_like_their_forward_slash_form() {
+    let repo = TempRepo::new();
+    repo.write("src/app/main.rs", "fn main() {}\n")
+        .write("src/lib.rs", "pub fn lib() {}\n")
+        .write("tests/it.rs", "#[test]\nfn it() {}\n");
+
+    let forward = repo.stdout(&["--include", "src/**/*.rs"]);
+    let backward = repo.stdout(&["--include", "src\\**\\*.rs"]);
+    assert_eq!(content_files(&forward), ["src/app/main.rs", "src/lib.rs"]);
+    assert_eq!(content_files(&backward), content_files(&forward));
+
+    let excluded = repo.stdout(&["--exclude", "src\\app\\**"]);
+    assert_eq!(content_files(&excluded), ["src/lib.rs", "tests/it.rs"]);
+}
+
+#[test]
+fn literal_backslash_keeps_backslashes_as_written() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "pub fn lib() {}\n");
+
+    let output = repo.stdout(&["--include", "src\\*.rs", "--literal-backslash"]);
+    assert!(content_files(&output).is_empty(), "{}", output);
+}
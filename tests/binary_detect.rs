@@ -0,0 +1,54 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn binary_detection_follows_the_mode_and_scan_window() {
+    let repo = TempRepo::new();
+    let mut late_nul = b"text\n".repeat(2000);
+    late_nul.extend([0, 1, 2, 3]);
+    repo.write("late.dat.txt", &late_nul);
+
+    let binary_marker = "<file src=\"late.dat.txt\" binary=\"true\"/>";
+    let default = repo.stdout(&[]);
+    assert!(!default.contains(binary_marker), "{}", default);
+    assert!(
+        default.contains("<file src=\"late.dat.txt\""),
+        "{}",
+        default
+    );
+
+    let widened = repo.stdout(&["--binary-scan-bytes", "20000"]);
+    assert!(widened.contains(binary_marker), "{}", widened);
+
+    let off = repo.stdout(&["--binary-detect", "off", "--binary-scan-bytes", "20000"]);
+    assert!(!off.contains(binary_marker), "{}", off);
+}
+
+#[test]
+fn a_file_with_nul_bytes_stays_in_the_tree_without_contents() {
+    let repo = TempRepo::new();
+    repo.write("image.png", b"PNG\x00\x00\x01\x02rest")
+        .write("notes.txt", "notes\n");
+
+    let output = repo.stdout(&[]);
+    assert!(output.contains("\"name\": \"image.png\""), "{}", output);
+    assert!(
+        output.contains("<file src=\"image.png\" binary=\"true\"/>"),
+        "{}",
+        output
+    );
+    assert!(!output.contains("rest"), "{}", output);
+    assert!(
+        output.contains("<file src=\"notes.txt\">\nnotes\n</file>"),
+        "{}",
+        output
+    );
+
+    let included = repo.stdout(&["--include-binary"]);
+    assert!(
+        included.contains("<file src=\"image.png\" lossy=\"true\">\nPNG\0\0\u{1}\u{2}rest</file>"),
+        "{}",
+        included
+    );
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::TempRepo;
+use serde_json::Value;
+
+/// A binary by content, a binary by `.gitattributes` and a text file.
+fn fixture() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write(".gitattributes", "*.dat binary\n")
+        .write("blob.bin", [0u8, 0, 1, 2, 3])
+        .write("marked.dat", "looks like text\n")
+        .write("plain.txt", "plain text\n");
+    repo
+}
+
+fn json_files(output: &str) -> Vec<Value> {
+    let document: Value = serde_json::from_str(output).expect("valid JSON document");
+    document["files"].as_array().expect("files array").clone()
+}
+
+fn json_file<'a>(files: &'a [Value], path: &str) -> &'a Value {
+    files
+        .iter()
+        .find(|file| file["path"] == path)
+        .unwrap_or_else(|| panic!("{} missing from {:?}", path, files))
+}
+
+#[test]
+fn binary_files_are_classified_the_same_in_every_format() {
+    let repo = fixture();
+
+    let xml = repo.stdout(&[]);
+    let files = json_files(&repo.stdout(&["--format", "json"]));
+    let markdown = repo.stdout(&["--format", "markdown"]);
+    for path in ["blob.bin", "marked.dat"] {
+        assert!(
+            xml.contains(&format!("<file src=\"{}\" binary=\"true\"/>", path)),
+            "{}",
+            xml
+        );
+        let file = json_file(&files, path);
+        assert_eq!(file["binary"], true, "{}", file);
+        assert!(file.get("content").is_none(), "{}", file);
+        assert!(
+            markdown.contains(&format!(
+                "\n## {}\n\nBinary file, contents omitted.\n",
+                path
+            )),
+            "{}",
+            markdown
+        );
+    }
+
+    assert!(
+        xml.contains("<file src=\"plain.txt\">\nplain text\n</file>"),
+        "{}",
+        xml
+    );
+    let plain = json_file(&files, "plain.txt");
+    assert!(plain.get("binary").is_none(), "{}", plain);
+    assert_eq!(plain["content"], "plain text\n");
+    assert!(
+        markdown.contains("\n## plain.txt\n\n```\nplain text\n```\n"),
+        "{}",
+        markdown
+    );
+}
+
+#[test]
+fn binary_base64_encodes_the_same_bytes_in_every_format() {
+    let repo = fixture();
+    let encoded = "AAABAgM=\n";
+
+    let xml = repo.stdout(&["--binary-base64"]);
+    assert!(
+        xml.contains(&format!(
+            "<file src=\"blob.bin\" encoding=\"base64\">\n{}</file>",
+            encoded
+        )),
+        "{}",
+        xml
+    );
+
+    let files = json_files(&repo.stdout(&["--format", "json", "--binary-base64"]));
+    let blob = json_file(&files, "blob.bin");
+    assert_eq!(blob["binary"], true, "{}", blob);
+    assert_eq!(blob["content_base64"], encoded, "{}", blob);
+
+    let markdown = repo.stdout(&["--format", "markdown", "--binary-base64"]);
+    assert!(
+        markdown.contains(&format!("\n## blob.bin\n\n```base64\n{}```\n", encoded)),
+        "{}",
+        markdown
+    );
+
+    let org = repo.stdout(&["--format", "org", "--binary-base64"]);
+    assert!(
+        org.contains(&format!(
+            "** blob.bin\n#+begin_src base64\n{}#+end_src\n",
+            encoded
+        )),
+        "{}",
+        org
+    );
+}
+
+#[test]
+fn xml_only_options_are_rejected_with_a_document_format() {
+    let repo = fixture();
+    for flag in [
+        &["--max-tokens", "10"][..],
+        &["--anchor-interval", "5"][..],
+        &["--cdata-for", "html"][..],
+        &["--note", "*.txt=hi"][..],
+        &["--fold-licenses"][..],
+        &["--annotate"][..],
+        &["--aggregate-small-files", "10"][..],
+    ] {
+        let output = repo.run(&[&["--format", "markdown"][..], flag].concat());
+        assert!(!output.status.success(), "{:?} was accepted", flag);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("cannot be used with"), "{}", stderr);
+    }
+}
+
+#[test]
+fn partial_on_error_reports_failed_reads_in_document_formats() {
+    let repo = fixture();
+    repo.write("broken.txt", b"caf\xe9\n");
+
+    let output = repo.run(&["--format", "json", "--partial-on-error"]);
+    assert_eq!(output.status.code(), Some(3));
+    let files = json_files(&String::from_utf8(output.stdout).expect("stdout is UTF-8"));
+    let broken = json_file(&files, "broken.txt");
+    assert!(broken["error"].is_string(), "{}", broken);
+    assert_eq!(json_file(&files, "plain.txt")["content"], "plain text\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("broken.txt"), "{}", stderr);
+}
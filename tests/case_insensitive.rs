@@ -0,0 +1,82 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn case_insensitive_applies_to_includes_and_excludes() {
+    let repo = TempRepo::new();
+    repo.write("src/Main.RS", "fn main() {}\n")
+        .write("NOTES.TXT", "notes\n");
+
+    let sensitive = repo.stdout(&["--include", "**/*.rs"]);
+    assert!(!sensitive.contains("fn main()"), "{}", sensitive);
+
+    let insensitive = repo.stdout(&[
+        "--case-insensitive",
+        "--include",
+        "**/*.rs",
+        "--include",
+        "*.txt",
+        "--exclude",
+        "notes.txt",
+    ]);
+    assert!(insensitive.contains("fn main()"), "{}", insensitive);
+    assert!(!insensitive.contains("notes\n"), "{}", insensitive);
+}
+
+#[test]
+fn ignore_case_is_an_alias_for_case_insensitive() {
+    let repo = TempRepo::new();
+    repo.write("README.md", "readme\n")
+        .write("src/lib.rs", "lib\n");
+
+    let sensitive = repo.stdout(&["--include", "*.MD"]);
+    assert!(
+        !sensitive.contains("<file src=\"README.md\""),
+        "{}",
+        sensitive
+    );
+
+    for flag in ["--case-insensitive", "--ignore-case"] {
+        let output = repo.stdout(&[flag, "--include", "*.MD"]);
+        assert!(output.contains("<file src=\"README.md\""), "{}", output);
+        assert!(!output.contains("<file src=\"src/lib.rs\""), "{}", output);
+    }
+}
+
+fn run_with_presets(repo: &TempRepo, presets: &str, args: &[&str]) -> String {
+    let path = repo.root.parent().unwrap().join("presets.toml");
+    std::fs::write(&path, presets).expect("write presets file");
+    let output = repo
+        .command(args)
+        .env("CODE_CONTEXT_CONFIG", &path)
+        .output()
+        .expect("run rust-context");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn a_preset_can_turn_on_case_insensitive_matching() {
+    let repo = TempRepo::new();
+    repo.write("src/Main.RS", "fn main() {}\n");
+    let sensitive = "[presets.app]\ninclude = [\"**/*.rs\"]\n";
+    let insensitive = "[presets.app]\ninclude = [\"**/*.rs\"]\ncase_insensitive = true\n";
+
+    let output = run_with_presets(&repo, sensitive, &["--preset", "app"]);
+    assert!(!output.contains("fn main()"), "{}", output);
+
+    let output = run_with_presets(&repo, insensitive, &["--preset", "app"]);
+    assert!(output.contains("fn main()"), "{}", output);
+
+    let top_level = "case_insensitive = true\n\n[presets.app]\ninclude = [\"**/*.rs\"]\n";
+    let output = run_with_presets(&repo, top_level, &["--preset", "app"]);
+    assert!(output.contains("fn main()"), "{}", output);
+
+    let output = run_with_presets(&repo, insensitive, &["--preset", "app", "--case-sensitive"]);
+    assert!(!output.contains("fn main()"), "{}", output);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn html_is_wrapped_in_cdata_while_rust_is_not() {
+    let repo = TempRepo::new();
+    repo.write("page.html", "<p>a & b</p>\n")
+        .write("main.rs", "fn f() -> bool { 1 < 2 }\n");
+
+    let output = repo.stdout(&[]);
+    assert!(
+        output.contains(
+            "<file src=\"page.html\" lang=\"html\">\n<![CDATA[\n<p>a & b</p>\n]]>\n</file>"
+        ),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("<file src=\"main.rs\" lang=\"rust\">\nfn f() -> bool { 1 < 2 }\n</file>"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn cdata_for_replaces_the_default_extensions() {
+    let repo = TempRepo::new();
+    repo.write("page.html", "<p>a & b</p>\n")
+        .write("main.rs", "fn f() -> bool { 1 < 2 }\n");
+
+    let output = repo.stdout(&["--cdata-for", "rs"]);
+    assert!(
+        output.contains("<file src=\"main.rs\" lang=\"rust\">\n<![CDATA[\nfn f()"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("<file src=\"page.html\" lang=\"html\">\n<p>a & b</p>\n</file>"),
+        "{}",
+        output
+    );
+
+    let output = repo.stdout(&["--cdata-for"]);
+    assert!(!output.contains("CDATA"), "{}", output);
+}
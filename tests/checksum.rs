@@ -0,0 +1,26 @@
+mod common;
+
+use common::TempRepo;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs;
+
+#[test]
+fn checksum_sidecar_matches_the_written_output() {
+    let repo = TempRepo::new();
+    repo.write("main.txt", "main\n");
+    let out_dir = repo.root.parent().unwrap();
+    let output_path = out_dir.join("context.txt");
+
+    repo.stdout(&["--output", output_path.to_str().unwrap(), "--checksum"]);
+
+    let written = fs::read(&output_path).expect("output written");
+    assert!(!written.is_empty());
+    let mut expected = String::new();
+    for byte in Sha256::digest(&written) {
+        let _ = write!(expected, "{:02x}", byte);
+    }
+    expected.push_str("  context.txt\n");
+    let sidecar = fs::read_to_string(out_dir.join("context.txt.sha256")).expect("sidecar written");
+    assert_eq!(sidecar, expected);
+}
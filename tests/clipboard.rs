@@ -0,0 +1,26 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn clipboard_falls_back_to_stdout_without_a_display() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "hello\n");
+
+    let output = repo
+        .command(&["--clipboard"])
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .expect("run rust-context");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout is UTF-8");
+    let stderr = String::from_utf8(output.stderr).expect("stderr is UTF-8");
+    assert!(
+        stdout.contains("<file src=\"a.txt\">\nhello\n"),
+        "{}",
+        stdout
+    );
+    assert!(stderr.contains("printing to stdout instead"), "{}", stderr);
+    assert!(!stderr.contains("Copied"), "{}", stderr);
+}
@@ -0,0 +1,138 @@
+#![allow(dead_code)]
+
+use git2::{IndexAddOption, Repository, Signature};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A throwaway git repository with its own home, config and cache
+/// directories, removed again when dropped.
+pub struct TempRepo {
+    base: PathBuf,
+    pub root: PathBuf,
+    pub repo: Repository,
+}
+
+impl TempRepo {
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let base = env::temp_dir().join(format!(
+            "rust-context-test-{}-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ));
+        let root = base.join("repo");
+        fs::create_dir_all(&root).expect("create temp repo");
+        fs::create_dir_all(base.join("home")).expect("create temp home");
+        let repo = Repository::init(&root).expect("init temp repo");
+        TempRepo { base, root, repo }
+    }
+
+    pub fn path(&self, relative_path: &str) -> PathBuf {
+        self.root.join(relative_path)
+    }
+
+    pub fn write(&self, relative_path: &str, contents: impl AsRef<[u8]>) -> &Self {
+        let path = self.path(relative_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent directory");
+        }
+        fs::write(path, contents).expect("write fixture file");
+        self
+    }
+
+    pub fn remove(&self, relative_path: &str) -> &Self {
+        fs::remove_file(self.path(relative_path)).expect("remove fixture file");
+        self
+    }
+
+    /// Stages the given paths (everything when empty) and commits them.
+    pub fn commit(&self, paths: &[&str], message: &str) -> &Self {
+        let mut index = self.repo.index().expect("open index");
+        if paths.is_empty() {
+            index
+                .add_all(["*"], IndexAddOption::DEFAULT, None)
+                .expect("stage files");
+        } else {
+            for path in paths {
+                index.add_path(Path::new(path)).expect("stage file");
+            }
+        }
+        index.write().expect("write index");
+        let tree = self
+            .repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("find tree");
+        let signature = Signature::now("Test", "test@example.com").expect("signature");
+        let parent = self
+            .repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        self.repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )
+            .expect("commit");
+        self
+    }
+
+    pub fn commit_all(&self, message: &str) -> &Self {
+        self.commit(&[], message)
+    }
+
+    /// The command for the binary, run from the repository root with home,
+    /// config and cache directories of its own.
+    pub fn command(&self, args: &[&str]) -> Command {
+        let home = self.base.join("home");
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rust-context"));
+        command
+            .current_dir(&self.root)
+            .args(args)
+            .env("HOME", &home)
+            .env("XDG_CONFIG_HOME", home.join(".config"))
+            .env("XDG_CACHE_HOME", home.join(".cache"))
+            .env_remove("CODE_CONTEXT_CONFIG");
+        command
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("run rust-context")
+    }
+
+    /// Stdout of a run that must succeed.
+    pub fn stdout(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "rust-context {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("stdout is UTF-8")
+    }
+
+    pub fn stderr(&self, args: &[&str]) -> String {
+        String::from_utf8(self.run(args).stderr).expect("stderr is UTF-8")
+    }
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.base);
+    }
+}
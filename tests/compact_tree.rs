@@ -0,0 +1,22 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn single_child_chains_collapse_while_branches_stay_nested() {
+    let repo = TempRepo::new();
+    repo.write("src/com/example/app/Main.java", "class Main {}\n")
+        .write("lib/a/one.txt", "")
+        .write("lib/b/two.txt", "");
+
+    assert_eq!(
+        repo.stdout(&["--tree", "--compact-tree"]),
+        "├── lib/\n\
+         │   ├── a/\n\
+         │   │   └── one.txt\n\
+         │   └── b/\n\
+         │       └── two.txt\n\
+         └── src/com/example/app/\n\
+         \x20   └── Main.java\n"
+    );
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn code_context_config_names_the_presets_file() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "a\n");
+
+    let output = repo
+        .command(&["--list-presets"])
+        .env("CODE_CONTEXT_CONFIG", repo.path("missing.toml"))
+        .output()
+        .expect("run rust-context");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Presets file not found"), "{}", stderr);
+    assert!(stderr.contains("missing.toml"), "{}", stderr);
+}
+
+fn with_presets<'a>(repo: &'a TempRepo, presets: &str) -> &'a TempRepo {
+    let path = repo.root.parent().unwrap().join("presets.toml");
+    std::fs::write(path, presets).expect("write presets file");
+    repo
+}
+
+fn run_with_presets(repo: &TempRepo, args: &[&str]) -> String {
+    let output = repo
+        .command(args)
+        .env(
+            "CODE_CONTEXT_CONFIG",
+            repo.root.parent().unwrap().join("presets.toml"),
+        )
+        .output()
+        .expect("run rust-context");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn a_detect_marker_selects_a_preset_named_unlike_the_folder() {
+    let repo = TempRepo::new();
+    repo.write("Cargo.toml", "[package]\n")
+        .write("src/main.rs", "fn main() {}\n")
+        .write("notes.md", "notes\n");
+    with_presets(
+        &repo,
+        "[presets.rust]\ninclude = [\"src/**\"]\n\n\
+         [presets.docs]\ninclude = [\"*.md\"]\n\n\
+         [detect.markers]\n\"Cargo.toml\" = \"rust\"\n",
+    );
+    assert_ne!(repo.root.file_name().unwrap(), "rust");
+
+    let output = run_with_presets(&repo, &["--meta-header"]);
+    assert!(output.contains(" preset=\"rust\""), "{}", output);
+    assert!(output.contains("<file src=\"src/main.rs\""), "{}", output);
+    assert!(!output.contains("<file src=\"notes.md\""), "{}", output);
+
+    let output = run_with_presets(&repo, &["--meta-header", "--preset", "docs"]);
+    assert!(output.contains(" preset=\"docs\""), "{}", output);
+    assert!(output.contains("<file src=\"notes.md\""), "{}", output);
+    assert!(!output.contains("<file src=\"src/main.rs\""), "{}", output);
+}
+
+#[test]
+fn a_typo_in_a_preset_is_reported_with_the_key_and_preset_name() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n");
+    let base = repo.root.parent().unwrap();
+
+    for (file, contents) in [
+        ("presets.toml", "[presets.web]\nincludes = [\"src/**\"]\n"),
+        (
+            "presets.yaml",
+            "presets:\n  web:\n    includes: [\"src/**\"]\n",
+        ),
+    ] {
+        let path = base.join(file);
+        std::fs::write(&path, contents).expect("write presets file");
+        let output = repo
+            .command(&["--preset", "web"])
+            .env("CODE_CONTEXT_CONFIG", &path)
+            .output()
+            .expect("run rust-context");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Invalid preset \"web\""), "{}", stderr);
+        assert!(stderr.contains("unknown field `includes`"), "{}", stderr);
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("fn main()"));
+    }
+}
+
+#[test]
+fn config_schema_documents_the_preset_keys() {
+    let repo = TempRepo::new();
+
+    let schema = repo.stdout(&["--config-schema"]);
+    assert!(schema.contains("[presets.<name>]"), "{}", schema);
+    assert!(schema.contains("Unknown keys are rejected."), "{}", schema);
+}
+
+fn content_files(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("<file src=\""))
+        .filter_map(|rest| rest.split('"').next())
+        .collect()
+}
+
+fn repo_with_web_preset() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n")
+        .write("src/debug.log", "noise\n")
+        .write("docs/guide.md", "guide\n")
+        .write("docs/build.log", "noise\n");
+    with_presets(
+        &repo,
+        "[presets.web]\ninclude = [\"src/**\"]\nexclude = [\"*.log\"]\n",
+    );
+    repo
+}
+
+#[test]
+fn cli_patterns_are_appended_to_the_preset_by_default() {
+    let repo = repo_with_web_preset();
+
+    let output = run_with_presets(&repo, &["--preset", "web", "--include", "docs/**"]);
+    assert_eq!(content_files(&output), ["docs/guide.md", "src/main.rs"]);
+}
+
+#[test]
+fn replace_swaps_only_the_lists_given_on_the_command_line() {
+    let repo = repo_with_web_preset();
+
+    let output = run_with_presets(
+        &repo,
+        &["--preset", "web", "--replace", "--include", "docs/**"],
+    );
+    // The preset's include is replaced, its exclude still applies.
+    assert_eq!(content_files(&output), ["docs/guide.md"]);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::TempRepo;
+use std::fs;
+
+/// Replaces the text of every cached entry, so a run that serves a file from
+/// the cache shows the marker instead of the file on disk.
+fn poison_cache(repo: &TempRepo) {
+    let dir = repo
+        .root
+        .parent()
+        .expect("temp base")
+        .join("home/.cache/rust-context/content");
+    let entries = fs::read_dir(&dir).expect("read cache directory");
+    let mut poisoned = 0;
+    for entry in entries {
+        let path = entry.expect("cache entry").path();
+        fs::write(&path, r#"{"content":"served from cache\n","lossy":false}"#)
+            .expect("overwrite cache entry");
+        poisoned += 1;
+    }
+    assert!(
+        poisoned > 0,
+        "the first run stored nothing in {}",
+        dir.display()
+    );
+}
+
+#[test]
+fn unchanged_files_are_served_from_the_content_cache() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "on disk\n");
+
+    let first = repo.stdout(&[]);
+    assert!(first.contains("on disk"), "{}", first);
+    poison_cache(&repo);
+
+    let cached = repo.stdout(&[]);
+    assert!(cached.contains("served from cache"), "{}", cached);
+    assert!(!cached.contains("on disk"), "{}", cached);
+
+    let uncached = repo.stdout(&["--no-content-cache"]);
+    assert!(uncached.contains("on disk"), "{}", uncached);
+}
+
+#[test]
+fn changed_files_and_settings_miss_the_content_cache() {
+    let repo = TempRepo::new();
+    repo.write("a.rs", "// note\nfn a() {}\n");
+
+    repo.stdout(&[]);
+    poison_cache(&repo);
+
+    // A different transform setting has its own entries.
+    let stripped = repo.stdout(&["--strip-comments"]);
+    assert!(stripped.contains("fn a() {}"), "{}", stripped);
+    assert!(!stripped.contains("served from cache"), "{}", stripped);
+
+    // So does a file whose size or modification time changed.
+    repo.write("a.rs", "fn b() {}\n");
+    let changed = repo.stdout(&[]);
+    assert!(changed.contains("fn b() {}"), "{}", changed);
+    assert!(!changed.contains("served from cache"), "{}", changed);
+}
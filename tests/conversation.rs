@@ -0,0 +1,38 @@
+mod common;
+
+use common::TempRepo;
+use serde_json::Value;
+
+#[test]
+fn json_conversation_puts_the_context_in_the_user_turn() {
+    let repo = TempRepo::new();
+    repo.write("notes.txt", "remember the milk\n");
+
+    let output = repo.stdout(&["--conversation", "json", "--system", "Be brief."]);
+    let messages: Value = serde_json::from_str(&output).expect("a JSON messages array");
+    let messages = messages.as_array().expect("an array");
+    assert_eq!(messages.len(), 2, "{}", output);
+
+    assert_eq!(messages[0]["role"], "system");
+    assert_eq!(messages[0]["content"], "Be brief.");
+    assert_eq!(messages[1]["role"], "user");
+    let content = messages[1]["content"].as_str().expect("string content");
+    assert!(content.starts_with("<directory-structure>"), "{}", content);
+    assert!(
+        content.contains("<file src=\"notes.txt\">\nremember the milk\n</file>"),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn json_conversation_without_a_system_preamble_has_only_the_user_turn() {
+    let repo = TempRepo::new();
+    repo.write("notes.txt", "remember the milk\n");
+
+    let output = repo.stdout(&["--conversation", "json"]);
+    let messages: Value = serde_json::from_str(&output).expect("a JSON messages array");
+    let messages = messages.as_array().expect("an array");
+    assert_eq!(messages.len(), 1, "{}", output);
+    assert_eq!(messages[0]["role"], "user");
+}
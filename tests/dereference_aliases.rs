@@ -0,0 +1,53 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn aliased_imports_are_annotated_with_the_resolved_file() {
+    let repo = TempRepo::new();
+    repo.write(
+        "tsconfig.json",
+        r#"{
+  // Comments and trailing commas are allowed here.
+  "compilerOptions": {
+    "baseUrl": ".",
+    "paths": { "@app/*": ["packages/app/src/*"], },
+  },
+}
+"#,
+    )
+    .write("packages/app/src/util.ts", "export const util = 1;\n")
+    .write(
+        "main.ts",
+        "import { util } from \"@app/util\";\nimport { other } from \"./other\";\n",
+    );
+
+    let output = repo.stdout(&["--dereference-aliases"]);
+    assert!(
+        output.contains(
+            "import { util } from \"@app/util\"; // -> packages/app/src/util.ts\n\
+             import { other } from \"./other\";\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn imports_are_left_as_is_without_the_flag() {
+    let repo = TempRepo::new();
+    repo.write(
+        "tsconfig.json",
+        r#"{ "compilerOptions": { "paths": { "@app/*": ["src/*"] } } }"#,
+    )
+    .write("src/util.ts", "export const util = 1;\n")
+    .write("main.ts", "import { util } from \"@app/util\";\n");
+
+    let output = repo.stdout(&[]);
+    assert!(
+        output.contains("import { util } from \"@app/util\";\n"),
+        "{}",
+        output
+    );
+    assert!(!output.contains("// ->"), "{}", output);
+}
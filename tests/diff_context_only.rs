@@ -0,0 +1,72 @@
+mod common;
+
+use common::TempRepo;
+
+fn numbered_lines(count: usize) -> String {
+    (1..=count).map(|n| format!("line {}\n", n)).collect()
+}
+
+#[test]
+fn diff_context_only_emits_just_the_changed_hunks() {
+    let repo = TempRepo::new();
+    repo.write("big.txt", numbered_lines(30))
+        .write("untouched.txt", "same\n")
+        .commit_all("initial");
+    repo.write(
+        "big.txt",
+        numbered_lines(30).replace("line 15\n", "changed 15\n"),
+    )
+    .write("added.txt", "new\n");
+
+    let output = repo.stdout(&["--diff-context-only", "HEAD"]);
+    assert!(
+        output.contains("<diff src=\"big.txt\" status=\"modified\">\n@@ -12,7 +12,7 @@"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains(" line 14\n-line 15\n+changed 15\n line 16\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("<diff src=\"added.txt\" status=\"added\">"),
+        "{}",
+        output
+    );
+    // Lines outside the context and unchanged files never appear.
+    assert!(!output.contains("line 1\n"), "{}", output);
+    assert!(!output.contains("line 30"), "{}", output);
+    assert!(!output.contains("untouched.txt"), "{}", output);
+    assert!(!output.contains("<file "), "{}", output);
+}
+
+#[test]
+fn diff_context_only_honours_the_globs() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "fn a() {}\n")
+        .write("notes.md", "old\n")
+        .commit_all("initial");
+    repo.write("src/lib.rs", "fn b() {}\n")
+        .write("notes.md", "new\n");
+
+    let output = repo.stdout(&["--diff-context-only", "HEAD", "--exclude", "*.md"]);
+    assert!(output.contains("+fn b() {}"), "{}", output);
+    assert!(!output.contains("notes.md"), "{}", output);
+}
+
+#[test]
+fn diff_modes_report_an_unknown_ref_by_name() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "a\n").commit_all("initial");
+
+    for flag in ["--diff-context-only", "--diff-stat", "--review"] {
+        let stderr = repo.stderr(&[flag, "no-such-ref"]);
+        assert!(
+            stderr.contains("Cannot resolve git ref no-such-ref"),
+            "{}: {}",
+            flag,
+            stderr
+        );
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn diff_stat_counts_added_and_removed_lines_per_file() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "one\ntwo\nthree\n")
+        .write("untouched.txt", "same\n")
+        .commit_all("initial");
+    repo.write("a.txt", "one\n2\n3\nfour\n")
+        .write("b.txt", "new\nfile\n");
+
+    let output = repo.stdout(&["--diff-stat", "HEAD"]);
+    assert!(
+        output.contains(
+            "<diff_stat>\n\
+             <file src=\"a.txt\" added=\"3\" removed=\"2\"/>\n\
+             <file src=\"b.txt\" added=\"2\" removed=\"0\"/>\n\
+             <total files=\"2\" added=\"5\" removed=\"2\"/>\n\
+             </diff_stat>\n"
+        ),
+        "{}",
+        output
+    );
+    assert!(
+        !output.contains("src=\"untouched.txt\" added"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn diff_stat_is_empty_without_changes() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "one\n").commit_all("initial");
+
+    let output = repo.stdout(&["--diff-stat", "HEAD"]);
+    assert!(
+        output.contains(
+            "<diff_stat>\n<total files=\"0\" added=\"0\" removed=\"0\"/>\n</diff_stat>\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn diff_stat_and_hunks_mark_changed_binary_files() {
+    let repo = TempRepo::new();
+    repo.write("logo.bin", [0u8, 0, 1, 2])
+        .write("a.txt", "one\n")
+        .commit_all("initial");
+    repo.write("logo.bin", [0u8, 0, 3, 4])
+        .write("a.txt", "two\n");
+
+    let stat = repo.stdout(&["--diff-stat", "HEAD"]);
+    assert!(
+        stat.contains("<file src=\"logo.bin\" binary=\"true\"/>\n"),
+        "{}",
+        stat
+    );
+    assert!(
+        stat.contains("<total files=\"2\" added=\"1\" removed=\"1\"/>"),
+        "{}",
+        stat
+    );
+
+    let hunks = repo.stdout(&["--diff-context-only", "HEAD"]);
+    assert!(
+        hunks.contains("<diff src=\"logo.bin\" status=\"modified\" binary=\"true\"/>\n"),
+        "{}",
+        hunks
+    );
+    assert!(hunks.contains("+two\n"), "{}", hunks);
+}
@@ -0,0 +1,16 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn latin1_files_are_decoded_to_utf8() {
+    let repo = TempRepo::new();
+    repo.write("legacy.txt", b"caf\xe9\n").commit_all("initial");
+
+    let output = repo.stdout(&["--encoding", "latin1"]);
+    assert!(
+        output.contains("<file src=\"legacy.txt\">\ncafé\n</file>"),
+        "{}",
+        output
+    );
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::TempRepo;
+
+fn file_order(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("<file src=\"")?.split('"').next())
+        .collect()
+}
+
+#[test]
+fn files_are_listed_in_natural_order_with_directories_first() {
+    let repo = TempRepo::new();
+    for path in [
+        "item10.txt",
+        "item2.txt",
+        "item1.txt",
+        "dir/a.txt",
+        "dir9/b.txt",
+    ] {
+        repo.write(path, "x\n");
+    }
+
+    assert_eq!(
+        file_order(&repo.stdout(&[])),
+        [
+            "dir/a.txt",
+            "dir9/b.txt",
+            "item1.txt",
+            "item2.txt",
+            "item10.txt"
+        ]
+    );
+    assert_eq!(
+        file_order(&repo.stdout(&["--files-first"])),
+        [
+            "item1.txt",
+            "item2.txt",
+            "item10.txt",
+            "dir/a.txt",
+            "dir9/b.txt"
+        ]
+    );
+}
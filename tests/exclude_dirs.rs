@@ -0,0 +1,42 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn excluded_directory_is_pruned_with_everything_below_it() {
+    let repo = TempRepo::new();
+    repo.write("vendor/dep.rs", "dep\n")
+        .write("vendor/nested/deep.rs", "deep\n")
+        .write("src/vendor.rs", "kept\n");
+
+    for pattern in ["vendor", "vendor/"] {
+        let output = repo.stdout(&["--exclude", pattern]);
+        assert!(output.contains("<file src=\"src/vendor.rs\""), "{}", output);
+        assert!(!output.contains("\"name\": \"vendor\""), "{}", output);
+        assert!(!output.contains("dep.rs"), "{}", output);
+        assert!(!output.contains("deep.rs"), "{}", output);
+    }
+
+    let report = repo.stdout(&["--exclude", "vendor", "--explain", "vendor/nested/deep.rs"]);
+    assert!(
+        report.contains("exclude: directory vendor matched \"vendor\""),
+        "{}",
+        report
+    );
+    assert!(report.ends_with("decision: skipped\n"), "{}", report);
+}
+
+#[test]
+fn force_include_still_reaches_into_an_excluded_directory() {
+    let repo = TempRepo::new();
+    repo.write("vendor/dep.rs", "dep\n")
+        .write("vendor/keep.rs", "keep\n");
+
+    let output = repo.stdout(&["--exclude", "vendor", "--force-include", "vendor/keep.rs"]);
+    assert!(
+        output.contains("<file src=\"vendor/keep.rs\""),
+        "{}",
+        output
+    );
+    assert!(!output.contains("dep.rs"), "{}", output);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn explains_an_excluded_and_an_included_path() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n")
+        .write("debug.log", "noise\n");
+
+    let excluded = repo.stdout(&["--exclude", "*.log", "--explain", "debug.log"]);
+    assert!(
+        excluded.contains("exclude: matched \"*.log\""),
+        "{}",
+        excluded
+    );
+    assert!(excluded.ends_with("decision: skipped\n"), "{}", excluded);
+
+    let included = repo.stdout(&["--include", "src/**", "--explain", "src/main.rs"]);
+    assert!(
+        included.contains("include: matched \"src/**\""),
+        "{}",
+        included
+    );
+    assert!(included.ends_with("decision: content\n"), "{}", included);
+}
+
+#[test]
+fn explains_gitignored_paths() {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", "build/\n")
+        .write("build/out.txt", "artifact\n");
+
+    let report = repo.stdout(&["--explain", "build/out.txt"]);
+    assert!(report.contains("gitignore: ignored"), "{}", report);
+    assert!(report.ends_with("decision: skipped\n"), "{}", report);
+}
+
+#[test]
+fn include_in_tree_alone_makes_every_file_tree_only() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "a\n").write("b.txt", "b\n");
+
+    let report = repo.stdout(&["--include-in-tree", "b.txt", "--explain", "b.txt"]);
+    assert!(report.ends_with("decision: tree-only\n"), "{}", report);
+    // The run itself agrees: no file has its contents emitted.
+    let output = repo.stdout(&["--include-in-tree", "b.txt"]);
+    assert!(!output.contains("<file "), "{}", output);
+}
+
+#[test]
+fn explains_paths_below_max_depth_as_skipped() {
+    let repo = TempRepo::new();
+    repo.write("a/b/deep.rs", "fn deep() {}\n");
+
+    let report = repo.stdout(&["--max-depth", "1", "--explain", "a/b/deep.rs"]);
+    assert!(report.contains("max-depth:"), "{}", report);
+    assert!(report.ends_with("decision: skipped\n"), "{}", report);
+}
+
+#[test]
+fn explains_binary_files() {
+    let repo = TempRepo::new();
+    repo.write("blob.dat", b"\x00\x01\x02binary");
+
+    let report = repo.stdout(&["--explain", "blob.dat"]);
+    assert!(
+        report.contains("binary: binary, contents omitted"),
+        "{}",
+        report
+    );
+    assert!(report.ends_with("decision: binary\n"), "{}", report);
+}
+
+#[test]
+fn explain_all_tabulates_every_path_with_its_decision() {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", "build/\n")
+        .write("build/out.txt", "artifact\n")
+        .write("src/main.rs", "fn main() {}\n")
+        .write("debug.log", "noise\n")
+        .write("schema.txt", "tables\n");
+
+    let args = [
+        "--explain-all",
+        "--include",
+        "src/**",
+        "--include-in-tree",
+        "schema.txt",
+        "--exclude",
+        "*.log",
+    ];
+    let mut tsv_args = args.to_vec();
+    tsv_args.push("--tsv");
+    assert_eq!(
+        repo.stdout(&tsv_args),
+        "path\tgitignored\texcluded\tincluded\ttree-only\tdecision\tsize\n\
+         .gitignore\tno\tno\tno\tno\tskipped\t7\n\
+         build/\tyes\tno\tno\tno\tskipped\t-\n\
+         debug.log\tno\tyes\tno\tno\tskipped\t6\n\
+         schema.txt\tno\tno\tno\tyes\ttree-only\t7\n\
+         src/main.rs\tno\tno\tyes\tno\tcontent\t13\n"
+    );
+
+    let table = repo.stdout(&args);
+    assert!(
+        table.starts_with(
+            "path         gitignored  excluded  included  tree-only  decision   size\n"
+        ),
+        "{}",
+        table
+    );
+    assert!(
+        table.contains("debug.log    no          yes       no        no         skipped    6\n"),
+        "{}",
+        table
+    );
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::TempRepo;
+
+fn scanned_repo() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write("keep.txt", "kept\n")
+        .write("scratch.tmp", "scratch\n")
+        .write("build/out.txt", "artifact\n");
+    repo
+}
+
+fn assert_external_rules_applied(output: &str) {
+    assert!(output.contains("\"name\": \"keep.txt\""), "{}", output);
+    assert!(!output.contains("scratch.tmp"), "{}", output);
+    assert!(!output.contains("\"name\": \"build\""), "{}", output);
+    assert!(!output.contains("artifact"), "{}", output);
+}
+
+#[test]
+fn applies_the_gitignore_of_another_repository() {
+    let repo = scanned_repo();
+    let other = TempRepo::new();
+    other.write(".gitignore", "*.tmp\nbuild/\n");
+
+    let output = repo.stdout(&[
+        "--exclude-by-gitignore-of",
+        other.root.to_str().expect("UTF-8 path"),
+    ]);
+    assert_external_rules_applied(&output);
+}
+
+#[test]
+fn applies_an_ignore_file_given_by_path() {
+    let repo = scanned_repo();
+    let other = TempRepo::new();
+    other.write("shared.ignore", "*.tmp\nbuild/\n");
+
+    let output = repo.stdout(&[
+        "--exclude-by-gitignore-of",
+        other.path("shared.ignore").to_str().expect("UTF-8 path"),
+    ]);
+    assert_external_rules_applied(&output);
+}
+
+#[test]
+fn a_missing_ignore_file_is_an_error() {
+    let repo = scanned_repo();
+    let other = TempRepo::new();
+
+    let stderr = repo.stderr(&[
+        "--exclude-by-gitignore-of",
+        other.root.to_str().expect("UTF-8 path"),
+    ]);
+    assert!(stderr.contains("Error loading ignore rules"), "{}", stderr);
+    assert!(stderr.contains(".gitignore"), "{}", stderr);
+}
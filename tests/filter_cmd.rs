@@ -0,0 +1,36 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn a_predicate_accepting_only_rust_files_demotes_the_rest_to_tree_only() {
+    let repo = TempRepo::new();
+    repo.write(
+        "only_rs.sh",
+        "case \"$1\" in *.rs) exit 0 ;; esac\nexit 1\n",
+    )
+    .write("src/lib.rs", "pub fn lib() {}\n")
+    .write("notes.txt", "notes\n")
+    .write("config.toml", "key = 1\n");
+
+    let output = repo.stdout(&["--filter-cmd", "sh only_rs.sh"]);
+    assert!(
+        output.contains("<file src=\"src/lib.rs\" lang=\"rust\">\npub fn lib() {}\n</file>"),
+        "{}",
+        output
+    );
+    for path in ["notes.txt", "config.toml", "only_rs.sh"] {
+        assert!(
+            output.contains(&format!("\"name\": \"{}\"", path)),
+            "{}",
+            output
+        );
+        assert!(
+            !output.contains(&format!("<file src=\"{}\"", path)),
+            "{}",
+            output
+        );
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::TempRepo;
+
+const HEADER: &str = "// Copyright (c) Example Corp.\n// Licensed under the MIT license.\n// See LICENSE for details.\n";
+
+#[test]
+fn fold_licenses_emits_a_shared_header_once() {
+    let repo = TempRepo::new();
+    for name in ["a.rs", "b.rs", "c.rs"] {
+        repo.write(name, format!("{}\nfn {}() {{}}\n", HEADER, &name[..1]));
+    }
+
+    let output = repo.stdout(&["--fold-licenses"]);
+    assert_eq!(
+        output.matches("Licensed under the MIT license").count(),
+        1,
+        "{}",
+        output
+    );
+    assert!(
+        output.contains(&format!("<license id=\"1\">\n{}</license>", HEADER)),
+        "{}",
+        output
+    );
+    assert_eq!(
+        output.matches("[license header 1]").count(),
+        3,
+        "{}",
+        output
+    );
+    assert!(output.contains("fn b() {}"), "{}", output);
+}
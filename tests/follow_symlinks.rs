@@ -0,0 +1,62 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::TempRepo;
+use std::os::unix::fs::symlink;
+
+#[test]
+fn two_links_to_one_file_emit_its_content_once() {
+    let repo = TempRepo::new();
+    repo.write("real/data.txt", "shared\n");
+    symlink("real/data.txt", repo.path("first.txt")).expect("create symlink");
+    symlink("real/data.txt", repo.path("second.txt")).expect("create symlink");
+
+    let output = repo.stdout(&["--follow-symlinks", "--exclude", "real/**"]);
+    assert!(
+        output.contains("<file src=\"first.txt\">\nshared\n</file>"),
+        "{}",
+        output
+    );
+    assert!(
+        output
+            .contains("<file src=\"second.txt\" same-as=\"first.txt\" target=\"real/data.txt\"/>"),
+        "{}",
+        output
+    );
+    assert_eq!(output.matches("shared\n").count(), 1, "{}", output);
+}
+
+#[test]
+fn a_symlinked_sibling_directory_is_walked_only_when_following_links() {
+    let repo = TempRepo::new();
+    repo.write("libs/lib.txt", "lib\n");
+    symlink("libs", repo.path("vendor")).expect("create symlink");
+    // Neither a link out of the root nor a loop back into it is followed.
+    let outside = repo.root.parent().unwrap().join("outside");
+    std::fs::create_dir_all(&outside).expect("create outside directory");
+    std::fs::write(outside.join("secret.txt"), "secret\n").expect("write outside file");
+    symlink(&outside, repo.path("ext")).expect("create symlink");
+    symlink(".", repo.path("loop")).expect("create symlink");
+
+    assert_eq!(
+        repo.stdout(&["--tree", "--follow-symlinks"]),
+        "├── libs/\n\
+         │   └── lib.txt\n\
+         └── vendor/\n\
+         \x20   └── lib.txt\n"
+    );
+    let output = repo.stdout(&["--follow-symlinks"]);
+    assert!(
+        output.contains("<file src=\"vendor/lib.txt\""),
+        "{}",
+        output
+    );
+    assert!(!output.contains("secret"), "{}", output);
+
+    assert_eq!(
+        repo.stdout(&["--tree"]),
+        "└── libs/\n\
+         \x20   └── lib.txt\n"
+    );
+}
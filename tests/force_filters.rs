@@ -0,0 +1,50 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn force_include_overrides_gitignore_and_excludes() {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", "build/\n*.log\n")
+        .write("build/generated.rs", "generated\n")
+        .write("build/other.rs", "other\n")
+        .write("debug.log", "log line\n")
+        .write("src/lib.rs", "lib\n");
+
+    let output = repo.stdout(&[
+        "--force-include",
+        "build/generated.rs",
+        "debug.log",
+        "--exclude",
+        "*.log",
+    ]);
+    assert!(
+        output.contains("<file src=\"build/generated.rs\""),
+        "{}",
+        output
+    );
+    assert!(output.contains("<file src=\"debug.log\""), "{}", output);
+    assert!(output.contains("<file src=\"src/lib.rs\""), "{}", output);
+    assert!(!output.contains("other.rs"), "{}", output);
+}
+
+#[test]
+fn force_exclude_overrides_includes_and_force_include() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "lib\n")
+        .write("src/secret.rs", "secret\n")
+        .write("vendor/dep.rs", "dep\n");
+
+    let output = repo.stdout(&[
+        "--include",
+        "src/**",
+        "--force-include",
+        "vendor/dep.rs",
+        "--force-exclude",
+        "src/secret.rs",
+        "vendor/**",
+    ]);
+    assert!(output.contains("<file src=\"src/lib.rs\""), "{}", output);
+    assert!(!output.contains("secret"), "{}", output);
+    assert!(!output.contains("dep.rs"), "{}", output);
+}
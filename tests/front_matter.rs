@@ -0,0 +1,50 @@
+mod common;
+
+use common::TempRepo;
+
+fn split_front_matter<'a>(output: &'a str, delimiter: &str) -> (&'a str, &'a str) {
+    let rest = output
+        .strip_prefix(&format!("{}\n", delimiter))
+        .expect("opening delimiter on the first line");
+    rest.split_once(&format!("\n{}\n", delimiter))
+        .expect("closing delimiter")
+}
+
+#[test]
+fn yaml_front_matter_parses_with_the_expected_keys() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "a\n").write("b.log", "b\n");
+
+    let output = repo.stdout(&["--front-matter", "yaml", "--exclude", "*.log"]);
+    let (front_matter, body) = split_front_matter(&output, "---");
+    let value: serde_yaml::Value = serde_yaml::from_str(front_matter).expect("valid YAML");
+    assert_eq!(value["tool"].as_str(), Some("rust-context"));
+    assert_eq!(value["files"].as_u64(), Some(1));
+    assert!(value["tokens"].as_u64().is_some_and(|tokens| tokens > 0));
+    assert!(value["root"].is_string());
+    assert_eq!(value["exclude"][0].as_str(), Some("*.log"));
+    assert!(value.get("generated_at").is_none());
+    assert!(body.starts_with("\n<directory-structure>"), "{}", body);
+}
+
+#[test]
+fn toml_front_matter_parses_with_the_expected_keys() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "a\n");
+
+    let output = repo.stdout(&["--front-matter", "toml", "--include", "*.txt"]);
+    let (front_matter, body) = split_front_matter(&output, "+++");
+    let value: toml::Table = front_matter.parse().expect("valid TOML");
+    assert_eq!(value["tool"].as_str(), Some("rust-context"));
+    assert_eq!(value["files"].as_integer(), Some(1));
+    assert!(
+        value["tokens"]
+            .as_integer()
+            .is_some_and(|tokens| tokens > 0)
+    );
+    assert_eq!(value["include"][0].as_str(), Some("*.txt"));
+    for key in ["version", "root", "include_in_tree", "tree_only", "exclude"] {
+        assert!(value.contains_key(key), "missing {}: {}", key, front_matter);
+    }
+    assert!(body.starts_with("\n<directory-structure>"), "{}", body);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::TempRepo;
+
+fn listed_files(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("<file src=\"")?.split('"').next())
+        .collect()
+}
+
+#[test]
+fn a_later_negation_overrides_an_earlier_include() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "lib\n")
+        .write("src/lib_test.rs", "test\n")
+        .write("src/nested/util_test.rs", "test\n")
+        .write("README.md", "readme\n");
+
+    let output = repo.stdout(&["--include", "src/**/*.rs", "--include", "!src/**/*_test.rs"]);
+    assert_eq!(listed_files(&output), ["src/lib.rs"]);
+}
+
+#[test]
+fn a_later_include_brings_a_negated_file_back() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "lib\n")
+        .write("src/lib_test.rs", "test\n")
+        .write("src/nested/util_test.rs", "test\n");
+
+    let output = repo.stdout(&[
+        "--include",
+        "src/**/*.rs",
+        "!src/**/*_test.rs",
+        "src/nested/util_test.rs",
+    ]);
+    assert_eq!(
+        listed_files(&output),
+        ["src/nested/util_test.rs", "src/lib.rs"]
+    );
+}
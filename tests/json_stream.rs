@@ -0,0 +1,80 @@
+mod common;
+
+use common::TempRepo;
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+
+fn repo_with_nested_files() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write("b.txt", "b\n")
+        .write("a.txt", "a\n")
+        .write("src/z.rs", "z\n")
+        .write("src/file10.rs", "ten\n")
+        .write("src/file2.rs", "two\n");
+    repo
+}
+
+fn record_paths(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| {
+            let record: Value = serde_json::from_str(line).expect("each line is a JSON object");
+            record["path"].as_str().expect("a path").to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn json_stream_emits_one_record_per_line_in_listing_order() {
+    let repo = repo_with_nested_files();
+
+    let output = repo.stdout(&["--json-stream"]);
+    assert_eq!(
+        record_paths(&output),
+        [
+            "src/file2.rs",
+            "src/file10.rs",
+            "src/z.rs",
+            "a.txt",
+            "b.txt"
+        ]
+    );
+    let first: Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+    assert_eq!(first["content"], "two\n");
+    assert_eq!(first["language"], "rust");
+
+    let output = repo.stdout(&["--json-stream", "--files-first"]);
+    assert_eq!(
+        record_paths(&output),
+        [
+            "a.txt",
+            "b.txt",
+            "src/file2.rs",
+            "src/file10.rs",
+            "src/z.rs"
+        ]
+    );
+}
+
+#[test]
+fn json_stream_records_can_be_read_one_at_a_time() {
+    let repo = repo_with_nested_files();
+
+    let mut child = repo
+        .command(&["--json-stream"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn rust-context");
+    let mut reader = BufReader::new(child.stdout.take().expect("piped stdout"));
+    let mut paths = Vec::new();
+    let mut line = String::new();
+    while reader.read_line(&mut line).expect("read a record") > 0 {
+        assert!(line.ends_with('\n'), "{:?}", line);
+        let record: Value = serde_json::from_str(&line).expect("a whole JSON object");
+        paths.push(record["path"].as_str().expect("a path").to_string());
+        line.clear();
+    }
+    assert!(child.wait().expect("wait for rust-context").success());
+    assert_eq!(paths.len(), 5, "{:?}", paths);
+}
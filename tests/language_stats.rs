@@ -0,0 +1,43 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn language_stats_break_down_bytes_by_language() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "x".repeat(200))
+        .write("src/lib.rs", "x".repeat(100))
+        .write("tool.py", "x".repeat(60))
+        .write("util.py", "x".repeat(40));
+
+    let output = repo.stdout(&["--language-stats"]);
+    assert_eq!(
+        output,
+        "Rust     75.00%  300 bytes\n\
+         Python   25.00%  100 bytes\n"
+    );
+
+    let total: f64 = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1)?.strip_suffix('%'))
+        .map(|percent| percent.parse::<f64>().expect("a percentage"))
+        .sum();
+    assert!((total - 100.0).abs() < 0.1, "{}", output);
+}
+
+#[test]
+fn uneven_percentages_still_sum_to_about_a_hundred() {
+    let repo = TempRepo::new();
+    repo.write("a.rs", "x".repeat(10))
+        .write("b.py", "x".repeat(10))
+        .write("c.go", "x".repeat(10));
+
+    let output = repo.stdout(&["--language-stats"]);
+    let percents: Vec<f64> = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1)?.strip_suffix('%'))
+        .map(|percent| percent.parse().expect("a percentage"))
+        .collect();
+    assert_eq!(percents, [33.33, 33.33, 33.33], "{}", output);
+    assert!((percents.iter().sum::<f64>() - 100.0).abs() < 0.1);
+}
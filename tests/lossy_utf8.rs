@@ -0,0 +1,33 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn lossy_utf8_replaces_invalid_bytes_and_warns() {
+    let repo = TempRepo::new();
+    repo.write("mostly_text.txt", b"ok \xff bad\n");
+
+    let output = repo.run(&["--lossy-utf8"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout is valid UTF-8");
+    assert!(
+        stdout.contains("<file src=\"mostly_text.txt\" lossy=\"true\">\nok \u{FFFD} bad\n</file>"),
+        "{}",
+        stdout
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("mostly_text.txt is not valid UTF-8, replacing invalid bytes"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn invalid_utf8_is_an_error_without_the_flag() {
+    let repo = TempRepo::new();
+    repo.write("mostly_text.txt", b"ok \xff bad\n");
+
+    let stderr = repo.stderr(&[]);
+    assert!(stderr.contains("is not valid UTF-8"), "{}", stderr);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::TempRepo;
+
+fn repo_with_sources() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n")
+        .write("notes.txt", "plain\n");
+    repo
+}
+
+#[test]
+fn org_wraps_files_in_src_blocks() {
+    let repo = repo_with_sources();
+
+    assert_eq!(
+        repo.stdout(&["--format", "org"]),
+        "* Directory structure\n\
+         #+begin_example\n\
+         ├── src/\n\
+         │   └── main.rs\n\
+         └── notes.txt\n\
+         #+end_example\n\
+         \n\
+         * Files\n\
+         ** src/main.rs\n\
+         #+begin_src rust\n\
+         fn main() {}\n\
+         #+end_src\n\
+         ** notes.txt\n\
+         #+begin_src text\n\
+         plain\n\
+         #+end_src\n"
+    );
+}
+
+#[test]
+fn typst_wraps_files_in_raw_blocks() {
+    let repo = repo_with_sources();
+
+    assert_eq!(
+        repo.stdout(&["--format", "typst"]),
+        "= Directory structure\n\
+         ```\n\
+         ├── src/\n\
+         │   └── main.rs\n\
+         └── notes.txt\n\
+         ```\n\
+         \n\
+         = Files\n\
+         == `src/main.rs`\n\
+         ```rust\n\
+         fn main() {}\n\
+         ```\n\
+         == `notes.txt`\n\
+         ```\n\
+         plain\n\
+         ```\n"
+    );
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use common::TempRepo;
+use std::fmt::Write as _;
+
+#[test]
+fn a_single_open_file_still_reads_every_file() {
+    let repo = TempRepo::new();
+    let mut expected = String::new();
+    for n in 0..64 {
+        let path = format!("d{}/f{:02}.txt", n / 16, n);
+        repo.write(&path, format!("contents of file {}\n", n));
+        let _ = write!(
+            expected,
+            "<file src=\"{}\">\ncontents of file {}\n</file>\n",
+            path, n
+        );
+    }
+
+    let output = repo.stdout(&["--max-open-files", "1"]);
+    let (_, contents) = output
+        .split_once("</directory-structure>\n\n")
+        .expect("tree before the contents");
+    assert_eq!(contents, expected);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn meta_header_lists_the_cli_and_preset_patterns_in_use() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n").write(
+        "presets.toml",
+        "[presets.web]\ninclude = [\"src/**\"]\nexclude = [\"dist/**\"]\n",
+    );
+
+    let output = repo
+        .command(&["--meta-header", "--preset", "web", "--exclude", "*.log"])
+        .env("CODE_CONTEXT_CONFIG", repo.path("presets.toml"))
+        .output()
+        .expect("run rust-context");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (header, _) = stdout.split_once("</meta>\n").expect("a meta block");
+    assert!(
+        header.starts_with("<meta tool=\"rust-context\""),
+        "{}",
+        header
+    );
+    assert!(header.contains(" preset=\"web\""), "{}", header);
+    assert!(
+        header.contains("<include pattern=\"src/**\"/>"),
+        "{}",
+        header
+    );
+    assert!(
+        header.contains("<exclude pattern=\"dist/**\"/>"),
+        "{}",
+        header
+    );
+    assert!(
+        header.contains("<exclude pattern=\"*.log\"/>"),
+        "{}",
+        header
+    );
+    assert!(!header.contains("generated-at"), "{}", header);
+}
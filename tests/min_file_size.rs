@@ -0,0 +1,18 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn files_below_the_minimum_size_are_demoted_to_tree_only() {
+    let repo = TempRepo::new();
+    repo.write("stub.rs", "mod a")
+        .write("real.txt", "x".repeat(200));
+
+    let output = repo.stdout(&["--min-file-size", "100"]);
+    assert!(output.contains("\"name\": \"stub.rs\""), "{}", output);
+    assert!(!output.contains("<file src=\"stub.rs\""), "{}", output);
+    assert!(output.contains("<file src=\"real.txt\""), "{}", output);
+
+    let output = repo.stdout(&[]);
+    assert!(output.contains("<file src=\"stub.rs\""), "{}", output);
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn a_gitignored_file_appears_only_with_no_gitignore() {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", "dist/\n")
+        .write("dist/bundle.js", "bundled();\n")
+        .write("dist/bundle.js.map", "{}\n")
+        .write("src/index.js", "run();\n");
+
+    let output = repo.stdout(&[]);
+    assert!(!output.contains("bundle.js"), "{}", output);
+    assert!(output.contains("<file src=\"src/index.js\""), "{}", output);
+
+    let output = repo.stdout(&["--no-gitignore", "--exclude", "*.map"]);
+    assert!(
+        output.contains("<file src=\"dist/bundle.js\" lang=\"javascript\">\nbundled();\n</file>"),
+        "{}",
+        output
+    );
+    assert!(!output.contains("bundle.js.map"), "{}", output);
+    assert!(!output.contains("\"name\": \".git\""), "{}", output);
+}
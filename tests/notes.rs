@@ -0,0 +1,38 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn a_note_is_attached_only_to_the_matching_file() {
+    let repo = TempRepo::new();
+    repo.write("src/target.rs", "fn target() {}\n")
+        .write("src/other.rs", "fn other() {}\n");
+
+    let output = repo.stdout(&["--note", "src/target.rs=this is the file to modify"]);
+    assert!(
+        output.contains(
+            "<file src=\"src/target.rs\" lang=\"rust\" note=\"this is the file to modify\">\n\
+             fn target() {}\n"
+        ),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("<file src=\"src/other.rs\" lang=\"rust\">\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn notes_are_escaped_as_attributes() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "a\n");
+
+    let output = repo.stdout(&["--note", "*.txt=use \"quotes\" & <tags>"]);
+    assert!(
+        output.contains("note=\"use &quot;quotes&quot; &amp; &lt;tags&gt;\""),
+        "{}",
+        output
+    );
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn the_overflow_warning_names_the_largest_file_first() {
+    let repo = TempRepo::new();
+    repo.write("small.txt", "tiny\n")
+        .write("huge.txt", "a".repeat(4000))
+        .write("medium.txt", "b".repeat(400));
+
+    let stderr = repo.stderr(&["--overflow-warn-tokens", "100"]);
+    assert!(stderr.contains("WARNING: output is large"), "{}", stderr);
+    let largest: Vec<&str> = stderr
+        .lines()
+        .skip_while(|line| !line.ends_with("Largest files:"))
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+    assert_eq!(
+        largest,
+        ["huge.txt", "medium.txt", "small.txt"],
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn no_overflow_warning_silences_it() {
+    let repo = TempRepo::new();
+    repo.write("huge.txt", "a".repeat(4000));
+
+    let stderr = repo.stderr(&["--overflow-warn-tokens", "100", "--no-overflow-warning"]);
+    assert!(!stderr.contains("WARNING"), "{}", stderr);
+}
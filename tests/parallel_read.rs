@@ -0,0 +1,30 @@
+mod common;
+
+use common::TempRepo;
+use std::fmt::Write as _;
+
+#[test]
+fn parallel_reads_keep_each_file_in_listing_order() {
+    let repo = TempRepo::new();
+    let mut expected = String::new();
+    for n in 0..200 {
+        let path = format!("d{}/f{:03}.txt", n / 50, n);
+        if n % 7 == 0 {
+            repo.write(&path, [0u8, 0, 1, 2, n as u8]);
+            let _ = writeln!(expected, "<file src=\"{}\" binary=\"true\"/>", path);
+        } else {
+            repo.write(&path, format!("contents of file {}\n", n));
+            let _ = write!(
+                expected,
+                "<file src=\"{}\">\ncontents of file {}\n</file>\n",
+                path, n
+            );
+        }
+    }
+
+    let output = repo.stdout(&[]);
+    let (_, contents) = output
+        .split_once("</directory-structure>\n\n")
+        .expect("tree before the contents");
+    assert_eq!(contents, expected);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::TempRepo;
+
+fn populated_repo() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", "target/\n*.log\n")
+        .write("README.md", "# readme\n")
+        .write("build.log", "ignored\n")
+        .write("target/out.txt", "ignored\n")
+        .write("vendor/lib/big.rs", "fn big() {}\n");
+    for dir in ["alpha", "beta", "gamma", "delta"] {
+        for file in ["a.rs", "nested/b.rs", "nested/deeper/c.txt"] {
+            repo.write(
+                &format!("{}/{}", dir, file),
+                format!("// {}/{}\n", dir, file),
+            );
+        }
+    }
+    repo
+}
+
+#[test]
+fn parallel_scan_output_matches_the_sequential_walk() {
+    let repo = populated_repo();
+    for extra in [
+        &[][..],
+        &["--exclude", "beta/**"][..],
+        &["--show-gitignored"][..],
+        &["--max-depth", "2"][..],
+        &["--skip-dirs-over", "2"][..],
+    ] {
+        let sequential = repo.stdout(extra);
+        let parallel = repo.stdout(&[extra, &["--parallel-scan"]].concat());
+        assert_eq!(sequential, parallel, "differs with {:?}", extra);
+    }
+}
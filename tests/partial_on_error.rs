@@ -0,0 +1,45 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn an_unreadable_file_is_skipped_and_reported_with_exit_status_3() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "first\n")
+        .write("bad.txt", b"ok \xff\n")
+        .write("c.txt", "last\n");
+
+    let output = repo.run(&["--partial-on-error"]);
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(
+            "<file src=\"a.txt\">\nfirst\n</file>\n<file src=\"c.txt\">\nlast\n</file>\n"
+        ),
+        "{}",
+        stdout
+    );
+    assert!(!stdout.contains("<file src=\"bad.txt\""), "{}", stdout);
+    assert!(
+        stdout.contains("<errors>\n<error src=\"bad.txt\" reason=\""),
+        "{}",
+        stdout
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Skipped bad.txt: "), "{}", stderr);
+    assert!(
+        stderr.contains("1 file(s) could not be read, output is partial"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn a_clean_run_with_partial_on_error_exits_zero() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "first\n");
+
+    let output = repo.run(&["--partial-on-error"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("<errors>"));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::TempRepo;
+use std::path::PathBuf;
+
+/// An output directory whose bundle paths are far over any platform's limit.
+fn long_output_dir(repo: &TempRepo) -> PathBuf {
+    let mut dir = repo.root.parent().expect("temp base").join("bundles");
+    for _ in 0..20 {
+        dir.push("d".repeat(250));
+    }
+    dir
+}
+
+#[test]
+fn an_over_long_output_path_is_warned_about() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n");
+    let output_dir = long_output_dir(&repo);
+
+    let stderr = repo.stderr(&[
+        "--split-by-dir",
+        "--output-dir",
+        output_dir.to_str().expect("utf-8 path"),
+    ]);
+    assert!(stderr.contains("Warning: "), "{}", stderr);
+    assert!(stderr.contains("-character path limit"), "{}", stderr);
+}
+
+#[test]
+fn strict_paths_rejects_an_over_long_output_path_before_writing() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {}\n");
+    let output_dir = long_output_dir(&repo);
+
+    let output = repo.run(&[
+        "--split-by-dir",
+        "--output-dir",
+        output_dir.to_str().expect("utf-8 path"),
+        "--strict-paths",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("over the platform limit"), "{}", stderr);
+    assert!(!stderr.contains("Warning: "), "{}", stderr);
+    assert!(!repo.root.parent().unwrap().join("bundles").exists());
+}
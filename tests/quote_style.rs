@@ -0,0 +1,43 @@
+mod common;
+
+use common::TempRepo;
+
+/// The value of every `src` attribute in `output`, unescaped, or `None` when
+/// a tag's quoting is broken.
+fn src_attributes(output: &str, quote: char) -> Option<Vec<String>> {
+    let marker = format!("<file src={}", quote);
+    output
+        .match_indices(&marker)
+        .map(|(start, _)| {
+            let value_start = start + marker.len();
+            let rest = &output[value_start..];
+            let end = rest.find(quote)?;
+            let after = rest[end + 1..].chars().next()?;
+            // The attribute must close cleanly before the next one or the tag end.
+            matches!(after, ' ' | '>' | '/').then(|| {
+                rest[..end]
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&amp;", "&")
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn a_path_with_a_double_quote_stays_well_formed() {
+    let repo = TempRepo::new();
+    repo.write("say \"hi\".txt", "hello\n");
+
+    for (style, quote) in [("double", '"'), ("single", '\'')] {
+        let output = repo.stdout(&["--quote-style", style]);
+        assert_eq!(
+            src_attributes(&output, quote),
+            Some(vec!["say \"hi\".txt".to_string()]),
+            "{}",
+            output
+        );
+    }
+}
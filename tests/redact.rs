@@ -0,0 +1,37 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn redact_from_replaces_custom_patterns_and_reports_counts() {
+    let repo = TempRepo::new();
+    repo.write(
+        "patterns.txt",
+        "# internal names\ndb01.corp.internal\nre:cust-[0-9]+\n",
+    )
+    .write(
+        "config.toml",
+        "host = \"db01.corp.internal\"\ncustomers = [\"cust-17\", \"cust-42\"]\n",
+    );
+
+    let output = repo.run(&["--redact-from", "patterns.txt", "--include", "config.toml"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stdout.contains("db01.corp.internal"), "{}", stdout);
+    assert!(!stdout.contains("cust-17"), "{}", stdout);
+    assert!(
+        stdout.contains("customers = [\"***REDACTED***\", \"***REDACTED***\"]"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stderr.contains("Redacted 1 match(es) of db01.corp.internal"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("Redacted 2 match(es) of re:cust-[0-9]+"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn review_composes_tree_changes_and_changed_contents() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "one\n")
+        .write("b.txt", "two\n")
+        .commit_all("initial");
+    repo.write("a.txt", "changed\n");
+
+    assert_eq!(
+        repo.stdout(&["--review", "HEAD"]),
+        "<directory-structure>\n\
+         ├── a.txt [M]\n\
+         └── b.txt\n\
+         </directory-structure>\n\
+         \n\
+         <changes>\n\
+         <diff src=\"a.txt\" status=\"modified\">\n\
+         @@ -1 +1 @@\n\
+         -one\n\
+         +changed\n\
+         </diff>\n\
+         </changes>\n\
+         \n\
+         <file src=\"a.txt\">\n\
+         changed\n\
+         </file>\n"
+    );
+}
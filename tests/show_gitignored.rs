@@ -0,0 +1,49 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn gitignored_files_are_marked_in_the_tree_without_contents() {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", ".env\nbuild/\n")
+        .write(".env", "SECRET=1\n")
+        .write("build/out.txt", "artifact\n")
+        .write("main.txt", "main\n");
+
+    let output = repo.stdout(&["--show-gitignored"]);
+    assert!(
+        output.contains("\"name\": \".env\",\n    \"type\": \"file\",\n    \"ignored\": true"),
+        "{}",
+        output
+    );
+    assert!(
+        output
+            .contains("\"name\": \"build\",\n    \"type\": \"directory\",\n    \"ignored\": true"),
+        "{}",
+        output
+    );
+    assert!(output.contains("<file src=\"main.txt\">"), "{}", output);
+    assert!(!output.contains("SECRET"), "{}", output);
+    assert!(!output.contains("artifact"), "{}", output);
+    assert!(!output.contains("<file src=\".env\""), "{}", output);
+
+    assert_eq!(
+        repo.stdout(&["--show-gitignored", "--tree"]),
+        "├── build/ [ignored]\n\
+         ├── .env [ignored]\n\
+         ├── .gitignore\n\
+         └── main.txt\n"
+    );
+}
+
+#[test]
+fn gitignored_files_are_left_out_without_the_flag() {
+    let repo = TempRepo::new();
+    repo.write(".gitignore", ".env\n")
+        .write(".env", "SECRET=1\n")
+        .write("main.txt", "main\n");
+
+    let output = repo.stdout(&[]);
+    assert!(!output.contains(".env\""), "{}", output);
+    assert!(!output.contains("SECRET"), "{}", output);
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn a_directory_over_the_limit_is_collapsed_while_siblings_are_listed() {
+    let repo = TempRepo::new();
+    for n in 0..5 {
+        repo.write(&format!("dataset/row{}.csv", n), "1,2,3\n");
+    }
+    repo.write("src/lib.rs", "pub fn lib() {}\n")
+        .write("src/main.rs", "fn main() {}\n")
+        .write("readme.txt", "hello\n");
+
+    assert_eq!(
+        repo.stdout(&["--skip-dirs-over", "3", "--tree"]),
+        "├── dataset/ [collapsed]\n\
+         ├── src/\n\
+         │   ├── lib.rs\n\
+         │   └── main.rs\n\
+         └── readme.txt\n"
+    );
+
+    let output = repo.stdout(&["--skip-dirs-over", "3"]);
+    assert!(!output.contains("1,2,3"), "{}", output);
+    assert!(output.contains("<file src=\"src/lib.rs\""), "{}", output);
+    assert!(output.contains("<file src=\"readme.txt\">"), "{}", output);
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn the_heaviest_directory_is_listed_first() {
+    let repo = TempRepo::new();
+    repo.write("alpha/small.txt", "x".repeat(10))
+        .write("beta/big.txt", "x".repeat(5000))
+        .write("gamma/mid.txt", "x".repeat(500))
+        .write("gamma/more.txt", "x".repeat(600))
+        .write("b.txt", "")
+        .write("a.txt", "");
+
+    assert_eq!(
+        repo.stdout(&["--tree", "--sort-dirs-by-size"]),
+        "├── beta/\n\
+         │   └── big.txt\n\
+         ├── gamma/\n\
+         │   ├── mid.txt\n\
+         │   └── more.txt\n\
+         ├── alpha/\n\
+         │   └── small.txt\n\
+         ├── a.txt\n\
+         └── b.txt\n"
+    );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn split_emits_every_file_once_in_order() {
+    let repo = TempRepo::new();
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        repo.write(name, format!("{}\n", name.repeat(100)));
+    }
+
+    let output = repo.stdout(&["--split", "300"]);
+    assert!(output.contains("of=\"3\""), "{}", output);
+    let positions: Vec<usize> = ["a.txt", "b.txt", "c.txt"]
+        .iter()
+        .map(|name| {
+            let tag = format!("<file src=\"{}\"", name);
+            assert_eq!(output.matches(&tag).count(), 1, "{}", output);
+            output.find(&tag).unwrap()
+        })
+        .collect();
+    assert!(positions.is_sorted(), "{}", output);
+}
+
+#[test]
+fn split_sizes_chunks_after_redaction() {
+    let repo = TempRepo::new();
+    let secret = "s".repeat(2000);
+    repo.write("patterns.txt", format!("{}\n", secret))
+        .write("a.txt", format!("key = {}\n", secret))
+        .write("b.txt", "plain\n");
+
+    let output = repo.run(&[
+        "--redact-from",
+        "patterns.txt",
+        "--exclude",
+        "patterns.txt",
+        "--split",
+        "400",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Redacted, a.txt is small enough to share a chunk with b.txt.
+    assert!(stdout.contains("of=\"1\""), "{}", stdout);
+    assert!(stdout.contains("***REDACTED***"), "{}", stdout);
+    assert!(!stderr.contains("over the --split budget"), "{}", stderr);
+    assert!(stderr.contains("Redacted 1 match(es)"), "{}", stderr);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::TempRepo;
+use std::fs;
+
+#[test]
+fn split_by_dir_writes_one_scoped_bundle_per_top_level_directory() {
+    let repo = TempRepo::new();
+    repo.write("README.md", "readme body\n")
+        .write("src/main.rs", "fn main() {}\n")
+        .write("src/util/mod.rs", "pub fn util() {}\n")
+        .write("docs/guide.md", "guide body\n");
+    let output_dir = repo.root.parent().expect("temp base").join("bundles");
+
+    repo.stdout(&[
+        "--split-by-dir",
+        "--output-dir",
+        output_dir.to_str().expect("utf-8 path"),
+    ]);
+
+    let mut names: Vec<String> = fs::read_dir(&output_dir)
+        .expect("read bundle directory")
+        .map(|entry| {
+            entry
+                .expect("bundle entry")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    names.sort();
+    assert_eq!(names, ["_root.txt", "docs.txt", "src.txt"]);
+
+    let bundle = |name: &str| fs::read_to_string(output_dir.join(name)).expect("read bundle");
+    let root_bundle = bundle("_root.txt");
+    assert!(root_bundle.contains("readme body"), "{}", root_bundle);
+    assert!(!root_bundle.contains("fn main"), "{}", root_bundle);
+    assert!(!root_bundle.contains("guide body"), "{}", root_bundle);
+
+    let src_bundle = bundle("src.txt");
+    assert!(
+        src_bundle.contains("<file src=\"src/main.rs\""),
+        "{}",
+        src_bundle
+    );
+    assert!(
+        src_bundle.contains("<file src=\"src/util/mod.rs\""),
+        "{}",
+        src_bundle
+    );
+    assert!(!src_bundle.contains("readme body"), "{}", src_bundle);
+    assert!(!src_bundle.contains("guide body"), "{}", src_bundle);
+
+    let docs_bundle = bundle("docs.txt");
+    assert!(docs_bundle.contains("guide body"), "{}", docs_bundle);
+    assert!(!docs_bundle.contains("fn main"), "{}", docs_bundle);
+    assert!(!docs_bundle.contains("readme body"), "{}", docs_bundle);
+}
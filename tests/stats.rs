@@ -0,0 +1,24 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn token_estimates_count_characters_not_bytes() {
+    let repo = TempRepo::new();
+    // Eight two-byte characters: 16 bytes, but only 2 tokens at 4 chars each.
+    repo.write("accents.txt", "éééééééé");
+
+    let stats = repo.stdout(&["--stats"]);
+    assert!(
+        stats.contains("Content bytes:       16 (~2 tokens)"),
+        "{}",
+        stats
+    );
+
+    let summary = repo.stdout(&["--summary"]);
+    assert!(
+        summary.contains("<summary files=\"1\" tree-only=\"0\" bytes=\"16\" tokens=\"2\"/>"),
+        "{}",
+        summary
+    );
+}
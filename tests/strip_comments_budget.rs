@@ -0,0 +1,38 @@
+mod common;
+
+use common::TempRepo;
+use std::fmt::Write as _;
+
+#[test]
+fn stripping_comments_brings_a_file_under_the_token_budget() {
+    let repo = TempRepo::new();
+    let mut source = String::new();
+    for n in 0..20 {
+        let _ = writeln!(source, "// comment line {} padded out to use up tokens", n);
+    }
+    source.push_str("fn main() {}\n");
+    repo.write("main.rs", source);
+
+    let output = repo.run(&["--max-tokens", "40"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("<file src=\"main.rs\""), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("over the 40-token budget: main.rs"),
+        "{}",
+        stderr
+    );
+
+    let output = repo.run(&["--max-tokens", "40", "--strip-comments"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("<file src=\"main.rs\" lang=\"rust\">\nfn main() {}\n</file>"),
+        "{}",
+        stdout
+    );
+    assert!(
+        !String::from_utf8_lossy(&output.stderr).contains("budget"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn tracked_only_lists_just_the_indexed_files() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "pub fn lib() {}\n")
+        .write("notes.md", "notes\n")
+        .commit_all("initial");
+    repo.write("artifact.bin.txt", "generated\n")
+        .write("src/scratch.rs", "fn scratch() {}\n");
+
+    let output = repo.stdout(&["--tracked-only"]);
+    assert!(output.contains("pub fn lib() {}"), "{}", output);
+    assert!(output.contains("\"name\": \"notes.md\""), "{}", output);
+    assert!(!output.contains("artifact.bin.txt"), "{}", output);
+    assert!(!output.contains("scratch"), "{}", output);
+
+    let narrowed = repo.stdout(&["--tracked-only", "--include", "src/**"]);
+    assert!(narrowed.contains("pub fn lib() {}"), "{}", narrowed);
+    assert!(!narrowed.contains("notes.md"), "{}", narrowed);
+    assert!(!narrowed.contains("scratch"), "{}", narrowed);
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn tree_lines_counts_files_and_totals_directories() {
+    let repo = TempRepo::new();
+    repo.write("src/main.rs", "fn main() {\n    run();\n}\n")
+        .write("src/util/mod.rs", "pub mod a;\npub mod b;\n")
+        .write("src/util/a.rs", "// no trailing newline")
+        .write("schema.sql", "create table t ();\n");
+
+    assert_eq!(
+        repo.stdout(&["--tree", "--tree-lines", "--tree-only", "*.sql"]),
+        "├── src/ (6)\n\
+         │   ├── util/ (3)\n\
+         │   │   ├── a.rs (1)\n\
+         │   │   └── mod.rs (2)\n\
+         │   └── main.rs (3)\n\
+         └── schema.sql\n"
+    );
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn tree_only_demotes_an_included_subpath() {
+    let repo = TempRepo::new();
+    repo.write("src/lib.rs", "pub fn lib() {}\n")
+        .write("src/generated/schema.rs", "pub struct Generated;\n");
+
+    let output = repo.stdout(&["--include", "src/**", "--tree-only", "src/generated/**"]);
+    assert!(output.contains("\"name\": \"schema.rs\""), "{}", output);
+    assert!(output.contains("<file src=\"src/lib.rs\""), "{}", output);
+    assert!(
+        !output.contains("<file src=\"src/generated/schema.rs\""),
+        "{}",
+        output
+    );
+    assert!(!output.contains("pub struct Generated"), "{}", output);
+}
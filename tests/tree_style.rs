@@ -0,0 +1,74 @@
+mod common;
+
+use common::TempRepo;
+
+#[test]
+fn tree_draws_connectors_for_nested_directories() {
+    let repo = TempRepo::new();
+    repo.write("a/x.txt", "")
+        .write("a/b/y.txt", "")
+        .write("a/b/z.txt", "")
+        .write("c.txt", "");
+
+    assert_eq!(
+        repo.stdout(&["--tree"]),
+        "├── a/\n\
+         │   ├── b/\n\
+         │   │   ├── y.txt\n\
+         │   │   └── z.txt\n\
+         │   └── x.txt\n\
+         └── c.txt\n"
+    );
+}
+
+#[test]
+fn tree_leaves_no_rule_below_a_last_directory() {
+    let repo = TempRepo::new();
+    repo.write("z/deep/f.txt", "").write("z/g.txt", "");
+
+    assert_eq!(
+        repo.stdout(&["--tree"]),
+        "└── z/\n\
+         \x20   ├── deep/\n\
+         \x20   │   └── f.txt\n\
+         \x20   └── g.txt\n"
+    );
+}
+
+#[test]
+fn reverse_flips_each_directory_of_the_tree() {
+    let repo = TempRepo::new();
+    repo.write("a/x.txt", "")
+        .write("a/y.txt", "")
+        .write("b/z.txt", "")
+        .write("c.txt", "")
+        .write("d.txt", "");
+
+    assert_eq!(
+        repo.stdout(&["--tree", "--reverse"]),
+        "├── b/\n\
+         │   └── z.txt\n\
+         ├── a/\n\
+         │   ├── y.txt\n\
+         │   └── x.txt\n\
+         ├── d.txt\n\
+         └── c.txt\n"
+    );
+}
+
+#[test]
+fn checkbox_markdown_nests_a_task_list() {
+    let repo = TempRepo::new();
+    repo.write("src/util/mod.rs", "")
+        .write("src/main.rs", "")
+        .write("README.md", "");
+
+    assert_eq!(
+        repo.stdout(&["--tree", "--tree-checkbox-markdown"]),
+        "- [ ] src/\n\
+         \x20 - [ ] util/\n\
+         \x20   - [ ] mod.rs\n\
+         \x20 - [ ] main.rs\n\
+         - [ ] README.md\n"
+    );
+}
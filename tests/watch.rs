@@ -0,0 +1,114 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::TempRepo;
+use std::fs;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Polls `check` until it holds or ten seconds have passed.
+fn wait_for(mut check: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if check() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+/// Sends Ctrl-C to the watcher and checks that it exits cleanly.
+fn interrupt(child: &mut Child) {
+    let interrupted = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("send SIGINT");
+    assert!(interrupted.success());
+    assert!(wait_for(|| child
+        .try_wait()
+        .expect("poll watcher")
+        .is_some()));
+    assert!(child.wait().expect("watcher status").success());
+}
+
+#[test]
+fn watch_regenerates_on_listed_changes_only_and_stops_on_ctrl_c() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "first\n").write("skip.log", "noise\n");
+    let output = repo.path("out.txt");
+
+    let mut child = repo
+        .command(&["--watch", "--exclude", "*.log", "--output", "out.txt"])
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("start watcher");
+    assert!(wait_for(
+        || fs::read_to_string(&output).is_ok_and(|text| text.contains("first"))
+    ));
+
+    // Removing the output is not a change, and neither is an excluded file.
+    fs::remove_file(&output).expect("remove output");
+    repo.write("skip.log", "more noise\n");
+    thread::sleep(Duration::from_secs(1));
+    assert!(!output.exists(), "regenerated after an excluded change");
+
+    repo.write("a.txt", "second\n");
+    assert!(wait_for(
+        || fs::read_to_string(&output).is_ok_and(|text| text.contains("second"))
+    ));
+
+    interrupt(&mut child);
+}
+
+#[test]
+fn on_change_only_skips_identical_output_on_stdout() {
+    let repo = TempRepo::new();
+    repo.write("a.txt", "first\n");
+
+    let mut child = repo
+        .command(&["--watch", "--on-change-only"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("start watcher");
+    let stdout = Arc::new(Mutex::new(String::new()));
+    let mut pipe = child.stdout.take().expect("watcher stdout");
+    let reader = {
+        let stdout = Arc::clone(&stdout);
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read) = pipe.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                let text = String::from_utf8_lossy(&buffer[..read]).into_owned();
+                stdout.lock().expect("stdout buffer").push_str(&text);
+            }
+        })
+    };
+    let emissions = || {
+        stdout
+            .lock()
+            .expect("stdout buffer")
+            .matches("<directory-structure>")
+            .count()
+    };
+    assert!(wait_for(|| emissions() == 1));
+
+    // Rewriting a listed file with the same bytes is an event with identical output.
+    repo.write("a.txt", "first\n");
+    thread::sleep(Duration::from_secs(1));
+    assert_eq!(emissions(), 1);
+
+    repo.write("a.txt", "second\n");
+    assert!(wait_for(|| emissions() == 2));
+    assert!(stdout.lock().expect("stdout buffer").contains("second"));
+
+    interrupt(&mut child);
+    reader.join().expect("stdout reader");
+}